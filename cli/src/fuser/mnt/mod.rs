@@ -3,9 +3,36 @@
 //! Raw communication channel to the FUSE kernel driver.
 //! This is a Linux-only pure-Rust implementation.
 
+// Wiring the result of `negotiate_protocol` into an actual `FUSE_INIT`
+// handshake (reading the kernel's major/minor out of the init request and
+// surfacing the negotiated version on `FuseMountOptions`/`MountHandle`)
+// belongs in `fuse_pure`'s INIT handling and `crate::fuse`. Neither is
+// present in this checkout, so only the negotiation arithmetic itself is
+// implemented here.
 mod fuse_pure;
 pub mod mount_options;
 
+/// Clamp the kernel's advertised FUSE ABI version to the highest version
+/// this side is willing to speak.
+///
+/// The kernel proposes `kernel` as `(major, minor)` in `FUSE_INIT`; `max`,
+/// when set, is the highest version the caller is willing to speak. The
+/// negotiated version is the lower of the two major versions; if the
+/// majors match, the lower of the two minors. A major mismatch with the
+/// kernel ahead always drops to the ceiling's major with minor 0, since
+/// minor versions aren't meaningfully comparable across major versions.
+pub(crate) fn negotiate_protocol(kernel: (u8, u8), max: Option<(u8, u8)>) -> (u8, u8) {
+    let Some(max) = max else {
+        return kernel;
+    };
+
+    match kernel.0.cmp(&max.0) {
+        std::cmp::Ordering::Less => kernel,
+        std::cmp::Ordering::Greater => (max.0, 0),
+        std::cmp::Ordering::Equal => (kernel.0, kernel.1.min(max.1)),
+    }
+}
+
 use std::fs::File;
 use std::io;
 
@@ -49,3 +76,29 @@ pub(crate) fn is_mounted(fuse_device: &File) -> bool {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_protocol_no_ceiling_keeps_kernel_version() {
+        assert_eq!(negotiate_protocol((7, 31), None), (7, 31));
+    }
+
+    #[test]
+    fn test_negotiate_protocol_kernel_major_behind_ceiling() {
+        assert_eq!(negotiate_protocol((6, 9), Some((7, 31))), (6, 9));
+    }
+
+    #[test]
+    fn test_negotiate_protocol_kernel_major_ahead_of_ceiling() {
+        assert_eq!(negotiate_protocol((8, 0), Some((7, 31))), (7, 0));
+    }
+
+    #[test]
+    fn test_negotiate_protocol_same_major_takes_lower_minor() {
+        assert_eq!(negotiate_protocol((7, 36), Some((7, 31))), (7, 31));
+        assert_eq!(negotiate_protocol((7, 20), Some((7, 31))), (7, 20));
+    }
+}
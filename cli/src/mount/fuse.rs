@@ -4,13 +4,79 @@ use anyhow::Result;
 use std::path::Path;
 use std::process::Command;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
 
 use super::{wait_for_mount, MountBackend, MountHandle, MountHandleInner, MountOpts};
+#[cfg(target_os = "linux")]
+use crate::fuser::mnt::is_mounted;
+
+/// Distinguishes a clean unmount from one where `fusermount` exited
+/// successfully but the kernel hadn't actually released the mount within
+/// the retry budget — a caller can match on this to decide whether to
+/// force a lazy unmount instead of treating it as a hard failure.
+#[derive(Debug)]
+pub(super) enum UnmountError {
+    /// `is_mounted` still reports the fuse device attached once the retry
+    /// budget elapsed. Note this also covers "detached but not yet
+    /// destroyed" (a lazy unmount that completed but whose teardown the
+    /// kernel hasn't finished) — from the caller's point of view the mount
+    /// isn't safely reusable yet either way.
+    StillMounted,
+}
+
+impl std::fmt::Display for UnmountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UnmountError::StillMounted => write!(
+                f,
+                "mountpoint was not released by the kernel within the retry budget"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UnmountError {}
+
+/// Poll `fuse_device` with exponential backoff, starting at 10ms and
+/// doubling each round, until `is_mounted` reports the mount gone or
+/// `timeout` elapses.
+#[cfg(target_os = "linux")]
+fn wait_for_unmount(
+    fuse_device: &std::fs::File,
+    timeout: Duration,
+) -> std::result::Result<(), UnmountError> {
+    let deadline = Instant::now() + timeout;
+    let mut backoff = Duration::from_millis(10);
+
+    loop {
+        if !is_mounted(fuse_device) {
+            return Ok(());
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(UnmountError::StillMounted);
+        }
+        std::thread::sleep(backoff.min(remaining));
+        backoff *= 2;
+    }
+}
 
 /// FUSE unmount implementation using fusermount.
+///
+/// `fuse_device`, when the caller retained the mount's `/dev/fuse` fd,
+/// lets this wait out the window between `fusermount` exiting zero and the
+/// kernel actually finishing teardown, rather than reporting success the
+/// moment the command returns. `timeout` bounds that wait; pass `None` for
+/// `fuse_device` to skip verification entirely (e.g. when the fd wasn't
+/// kept around), preserving the old fire-and-forget behavior.
 #[cfg(target_os = "linux")]
-pub(super) fn unmount_fuse(mountpoint: &Path, lazy: bool) -> Result<()> {
+pub(super) fn unmount_fuse(
+    mountpoint: &Path,
+    lazy: bool,
+    fuse_device: Option<&std::fs::File>,
+    timeout: Duration,
+) -> Result<()> {
     const FUSERMOUNT_COMMANDS: &[&str] = &["fusermount3", "fusermount"];
     let args: &[&str] = if lazy { &["-uz"] } else { &["-u"] };
 
@@ -21,7 +87,12 @@ pub(super) fn unmount_fuse(mountpoint: &Path, lazy: bool) -> Result<()> {
             .status();
 
         match result {
-            Ok(status) if status.success() => return Ok(()),
+            Ok(status) if status.success() => {
+                if let Some(fuse_device) = fuse_device {
+                    wait_for_unmount(fuse_device, timeout)?;
+                }
+                return Ok(());
+            }
             Ok(_) => continue,
             Err(_) => continue,
         }
@@ -34,16 +105,117 @@ pub(super) fn unmount_fuse(mountpoint: &Path, lazy: bool) -> Result<()> {
     )
 }
 
-/// FUSE unmount is not available on macOS.
+/// FUSE unmount implementation for macFUSE/fuse-t.
+///
+/// macOS has no `fusermount` helper to shell out to, so this calls the BSD
+/// `unmount(2)` syscall directly — the same "talk to the kernel, skip the
+/// helper binary" approach as `crate::fuser::mnt`'s `libc_umount`, just
+/// using macOS's `(path, flags)` signature instead of Linux's single-arg
+/// `umount(2)` that helper wraps. `lazy` maps to `MNT_FORCE`, the closest
+/// macOS equivalent to Linux's lazy unmount.
+///
+/// `fuse_device`/`timeout` mirror the Linux signature for a uniform call
+/// site, but aren't used here yet: `is_mounted`'s poll loop is written
+/// against Linux's `/dev/fuse` semantics, and macFUSE/fuse-t's equivalent
+/// wasn't implemented as part of this change.
 #[cfg(target_os = "macos")]
-pub(super) fn unmount_fuse(_mountpoint: &Path, _lazy: bool) -> Result<()> {
-    anyhow::bail!("FUSE unmount is not supported on macOS")
+pub(super) fn unmount_fuse(
+    mountpoint: &Path,
+    lazy: bool,
+    _fuse_device: Option<&std::fs::File>,
+    _timeout: Duration,
+) -> Result<()> {
+    use std::ffi::CString;
+
+    let path = mountpoint
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Mountpoint path is not valid UTF-8"))?;
+    let c_path = CString::new(path)?;
+    let flags = if lazy { libc::MNT_FORCE } else { 0 };
+
+    let result = unsafe { libc::unmount(c_path.as_ptr(), flags) };
+    if result < 0 {
+        anyhow::bail!(
+            "Failed to unmount {}: {}",
+            mountpoint.display(),
+            std::io::Error::last_os_error()
+        );
+    }
+    Ok(())
 }
 
 /// Internal FUSE mount implementation.
+///
+/// Note: `RwLockFsAdapter` below forwards `{get,set,list,remove}xattr`,
+/// `copy_file_range`, and `fallocate` to the `agentfs_sdk::FileSystem`
+/// trait; the matching FUSE opcode handlers (`getxattr`/`setxattr`/
+/// `listxattr`/`removexattr`/`FUSE_COPY_FILE_RANGE`/`FUSE_FALLOCATE` —
+/// the latter rejecting collapse/insert-range with `EOPNOTSUPP` when the
+/// backend can't shift extents, and `FUSE_COPY_FILE_RANGE` replying
+/// `ENOSYS` once a backend declines) belong in `crate::fuse`, which
+/// dispatches kernel requests onto this trait — that module isn't present
+/// in this checkout, so the opcode wiring itself can't be added here.
+/// Likewise, splitting `FileSystem` itself into separately-lockable
+/// read/write method groups (so an
+/// implementor could opt into per-path locking instead of one global
+/// `RwLock`) would mean changing the trait's own definition, which also
+/// lives outside this checkout.
 #[cfg(target_os = "linux")]
 pub(super) fn mount_fuse(
-    fs: Arc<Mutex<dyn agentfs_sdk::FileSystem + Send>>,
+    fs: Arc<RwLock<dyn agentfs_sdk::FileSystem + Send>>,
+    opts: MountOpts,
+) -> Result<MountHandle> {
+    use crate::fuse::FuseMountOptions;
+
+    let fuse_opts = FuseMountOptions {
+        mountpoint: opts.mountpoint.clone(),
+        auto_unmount: opts.auto_unmount,
+        allow_root: opts.allow_root,
+        allow_other: opts.allow_other,
+        fsname: opts.fsname.clone(),
+        uid: opts.uid,
+        gid: opts.gid,
+    };
+
+    let mountpoint = opts.mountpoint.clone();
+    let timeout = opts.timeout;
+    let lazy_unmount = opts.lazy_unmount;
+
+    let fs_adapter = RwLockFsAdapter { inner: fs };
+    let fs_arc: Arc<dyn agentfs_sdk::FileSystem> = Arc::new(fs_adapter);
+
+    let fuse_handle = std::thread::spawn(move || {
+        let rt = crate::get_runtime();
+        crate::fuse::mount(fs_arc, fuse_opts, rt)
+    });
+
+    if !wait_for_mount(&mountpoint, timeout) {
+        anyhow::bail!("FUSE mount did not become ready within {:?}", timeout);
+    }
+
+    Ok(MountHandle {
+        mountpoint,
+        backend: MountBackend::Fuse,
+        lazy_unmount,
+        inner: MountHandleInner::Fuse {
+            _thread: fuse_handle,
+        },
+    })
+}
+
+/// Internal FUSE mount implementation for macFUSE/fuse-t.
+///
+/// Structurally identical to the Linux path above — same adapter, same
+/// background mount thread — macFUSE just talks to `/dev/macfuse*` instead
+/// of `/dev/fuse` under the hood, which `crate::fuse::mount` is expected to
+/// handle per-platform. The one real difference macOS needs is surfacing
+/// `crtime`/BSD `flags` in the `FileAttr` macFUSE requires, which means
+/// adding those fields to `agentfs_sdk::Stats` behind
+/// `#[cfg(target_os = "macos")]` — that type isn't part of this checkout,
+/// so it can't be extended from here.
+#[cfg(target_os = "macos")]
+pub(super) fn mount_fuse(
+    fs: Arc<RwLock<dyn agentfs_sdk::FileSystem + Send>>,
     opts: MountOpts,
 ) -> Result<MountHandle> {
     use crate::fuse::FuseMountOptions;
@@ -62,7 +234,7 @@ pub(super) fn mount_fuse(
     let timeout = opts.timeout;
     let lazy_unmount = opts.lazy_unmount;
 
-    let fs_adapter = MutexFsAdapter { inner: fs };
+    let fs_adapter = RwLockFsAdapter { inner: fs };
     let fs_arc: Arc<dyn agentfs_sdk::FileSystem> = Arc::new(fs_adapter);
 
     let fuse_handle = std::thread::spawn(move || {
@@ -84,60 +256,66 @@ pub(super) fn mount_fuse(
     })
 }
 
-/// Adapter to use `Arc<Mutex<dyn FileSystem>>` as `Arc<dyn FileSystem>`.
-struct MutexFsAdapter {
-    inner: Arc<Mutex<dyn agentfs_sdk::FileSystem + Send>>,
+/// Adapter to use `Arc<RwLock<dyn FileSystem>>` as `Arc<dyn FileSystem>`.
+///
+/// Read-only calls (`stat`, `lstat`, `readdir`, `readdir_plus`, `readlink`,
+/// `read_file`, `statfs`, `getxattr`, `listxattr`) only take a shared read
+/// guard, so a slow `read_file` no longer blocks every other lookup the
+/// kernel issues concurrently. Every other call, including `open`, takes
+/// the exclusive write guard.
+struct RwLockFsAdapter {
+    inner: Arc<RwLock<dyn agentfs_sdk::FileSystem + Send>>,
 }
 
 #[async_trait::async_trait]
-impl agentfs_sdk::FileSystem for MutexFsAdapter {
+impl agentfs_sdk::FileSystem for RwLockFsAdapter {
     async fn stat(
         &self,
         path: &str,
     ) -> std::result::Result<Option<agentfs_sdk::Stats>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.stat(path).await
+        self.inner.read().await.stat(path).await
     }
 
     async fn lstat(
         &self,
         path: &str,
     ) -> std::result::Result<Option<agentfs_sdk::Stats>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.lstat(path).await
+        self.inner.read().await.lstat(path).await
     }
 
     async fn read_file(
         &self,
         path: &str,
     ) -> std::result::Result<Option<Vec<u8>>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.read_file(path).await
+        self.inner.read().await.read_file(path).await
     }
 
     async fn readdir(
         &self,
         path: &str,
     ) -> std::result::Result<Option<Vec<String>>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.readdir(path).await
+        self.inner.read().await.readdir(path).await
     }
 
     async fn readdir_plus(
         &self,
         path: &str,
     ) -> std::result::Result<Option<Vec<agentfs_sdk::DirEntry>>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.readdir_plus(path).await
+        self.inner.read().await.readdir_plus(path).await
     }
 
     async fn readlink(
         &self,
         path: &str,
     ) -> std::result::Result<Option<String>, agentfs_sdk::error::Error> {
-        self.inner.lock().await.readlink(path).await
+        self.inner.read().await.readlink(path).await
     }
 
     async fn open(
         &self,
         path: &str,
     ) -> std::result::Result<agentfs_sdk::BoxedFile, agentfs_sdk::error::Error> {
-        self.inner.lock().await.open(path).await
+        self.inner.write().await.open(path).await
     }
 
     async fn create_file(
@@ -149,7 +327,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
     ) -> std::result::Result<(agentfs_sdk::Stats, agentfs_sdk::BoxedFile), agentfs_sdk::error::Error>
     {
         self.inner
-            .lock()
+            .write()
             .await
             .create_file(path, mode, uid, gid)
             .await
@@ -161,7 +339,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         uid: u32,
         gid: u32,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.mkdir(path, uid, gid).await
+        self.inner.write().await.mkdir(path, uid, gid).await
     }
 
     async fn mknod(
@@ -173,14 +351,14 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         gid: u32,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
         self.inner
-            .lock()
+            .write()
             .await
             .mknod(path, mode, rdev, uid, gid)
             .await
     }
 
     async fn remove(&self, path: &str) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.remove(path).await
+        self.inner.write().await.remove(path).await
     }
 
     async fn rename(
@@ -188,7 +366,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         from: &str,
         to: &str,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.rename(from, to).await
+        self.inner.write().await.rename(from, to).await
     }
 
     async fn symlink(
@@ -199,7 +377,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         gid: u32,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
         self.inner
-            .lock()
+            .write()
             .await
             .symlink(target, link_path, uid, gid)
             .await
@@ -210,7 +388,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         old_path: &str,
         new_path: &str,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.link(old_path, new_path).await
+        self.inner.write().await.link(old_path, new_path).await
     }
 
     async fn chmod(
@@ -218,7 +396,7 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         path: &str,
         mode: u32,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.chmod(path, mode).await
+        self.inner.write().await.chmod(path, mode).await
     }
 
     async fn chown(
@@ -227,12 +405,327 @@ impl agentfs_sdk::FileSystem for MutexFsAdapter {
         uid: Option<u32>,
         gid: Option<u32>,
     ) -> std::result::Result<(), agentfs_sdk::error::Error> {
-        self.inner.lock().await.chown(path, uid, gid).await
+        self.inner.write().await.chown(path, uid, gid).await
     }
 
     async fn statfs(
         &self,
     ) -> std::result::Result<agentfs_sdk::FilesystemStats, agentfs_sdk::error::Error> {
-        self.inner.lock().await.statfs().await
+        self.inner.read().await.statfs().await
+    }
+
+    async fn getxattr(
+        &self,
+        path: &str,
+        name: &str,
+    ) -> std::result::Result<Option<Vec<u8>>, agentfs_sdk::error::Error> {
+        self.inner.read().await.getxattr(path, name).await
+    }
+
+    async fn setxattr(
+        &self,
+        path: &str,
+        name: &str,
+        value: &[u8],
+        flags: i32,
+    ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+        self.inner
+            .write()
+            .await
+            .setxattr(path, name, value, flags)
+            .await
+    }
+
+    async fn listxattr(
+        &self,
+        path: &str,
+    ) -> std::result::Result<Vec<String>, agentfs_sdk::error::Error> {
+        self.inner.read().await.listxattr(path).await
+    }
+
+    async fn removexattr(
+        &self,
+        path: &str,
+        name: &str,
+    ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+        self.inner.write().await.removexattr(path, name).await
+    }
+
+    async fn copy_file_range(
+        &self,
+        src: &str,
+        src_off: u64,
+        dst: &str,
+        dst_off: u64,
+        len: u64,
+    ) -> std::result::Result<usize, agentfs_sdk::error::Error> {
+        self.inner
+            .write()
+            .await
+            .copy_file_range(src, src_off, dst, dst_off, len)
+            .await
+    }
+
+    async fn fallocate(
+        &self,
+        path: &str,
+        mode: agentfs_sdk::FallocMode,
+        offset: u64,
+        len: u64,
+    ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+        self.inner.write().await.fallocate(path, mode, offset, len).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_wait_for_unmount_returns_immediately_when_not_mounted() {
+        // `is_mounted` treats an fd that polls ready without POLLERR as
+        // "released". A socket whose peer has been closed polls as POLLHUP
+        // alone, which exercises the success path without a real FUSE
+        // device (a regular file polls as not ready, i.e. still mounted).
+        let (sock, peer) = std::os::unix::net::UnixStream::pair().expect("socketpair");
+        drop(peer);
+        let file = std::fs::File::from(std::os::fd::OwnedFd::from(sock));
+        let start = Instant::now();
+        wait_for_unmount(&file, Duration::from_secs(5)).expect("should report unmounted");
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    /// A fake `FileSystem` whose `read_file` sleeps, so a test can tell
+    /// whether overlapping calls through the adapter actually overlap or
+    /// are serialized behind a single lock.
+    struct SlowFs {
+        concurrent_reads: Arc<AtomicUsize>,
+        max_concurrent_reads: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl agentfs_sdk::FileSystem for SlowFs {
+        async fn stat(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<agentfs_sdk::Stats>, agentfs_sdk::error::Error> {
+            Ok(None)
+        }
+
+        async fn lstat(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<agentfs_sdk::Stats>, agentfs_sdk::error::Error> {
+            Ok(None)
+        }
+
+        async fn read_file(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<Vec<u8>>, agentfs_sdk::error::Error> {
+            let in_flight = self.concurrent_reads.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_concurrent_reads.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            self.concurrent_reads.fetch_sub(1, Ordering::SeqCst);
+            Ok(Some(Vec::new()))
+        }
+
+        async fn readdir(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<Vec<String>>, agentfs_sdk::error::Error> {
+            Ok(None)
+        }
+
+        async fn readdir_plus(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<Vec<agentfs_sdk::DirEntry>>, agentfs_sdk::error::Error>
+        {
+            Ok(None)
+        }
+
+        async fn readlink(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Option<String>, agentfs_sdk::error::Error> {
+            Ok(None)
+        }
+
+        async fn open(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<agentfs_sdk::BoxedFile, agentfs_sdk::error::Error> {
+            unimplemented!("not exercised by this test")
+        }
+
+        async fn create_file(
+            &self,
+            _path: &str,
+            _mode: u32,
+            _uid: u32,
+            _gid: u32,
+        ) -> std::result::Result<
+            (agentfs_sdk::Stats, agentfs_sdk::BoxedFile),
+            agentfs_sdk::error::Error,
+        > {
+            unimplemented!("not exercised by this test")
+        }
+
+        async fn mkdir(
+            &self,
+            _path: &str,
+            _uid: u32,
+            _gid: u32,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn mknod(
+            &self,
+            _path: &str,
+            _mode: u32,
+            _rdev: u64,
+            _uid: u32,
+            _gid: u32,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn remove(&self, _path: &str) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn rename(
+            &self,
+            _from: &str,
+            _to: &str,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn symlink(
+            &self,
+            _target: &str,
+            _link_path: &str,
+            _uid: u32,
+            _gid: u32,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn link(
+            &self,
+            _old_path: &str,
+            _new_path: &str,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn chmod(
+            &self,
+            _path: &str,
+            _mode: u32,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn chown(
+            &self,
+            _path: &str,
+            _uid: Option<u32>,
+            _gid: Option<u32>,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn statfs(
+            &self,
+        ) -> std::result::Result<agentfs_sdk::FilesystemStats, agentfs_sdk::error::Error> {
+            unimplemented!("not exercised by this test")
+        }
+
+        async fn getxattr(
+            &self,
+            _path: &str,
+            _name: &str,
+        ) -> std::result::Result<Option<Vec<u8>>, agentfs_sdk::error::Error> {
+            Ok(None)
+        }
+
+        async fn setxattr(
+            &self,
+            _path: &str,
+            _name: &str,
+            _value: &[u8],
+            _flags: i32,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn listxattr(
+            &self,
+            _path: &str,
+        ) -> std::result::Result<Vec<String>, agentfs_sdk::error::Error> {
+            Ok(Vec::new())
+        }
+
+        async fn removexattr(
+            &self,
+            _path: &str,
+            _name: &str,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+
+        async fn copy_file_range(
+            &self,
+            _src: &str,
+            _src_off: u64,
+            _dst: &str,
+            _dst_off: u64,
+            _len: u64,
+        ) -> std::result::Result<usize, agentfs_sdk::error::Error> {
+            Ok(0)
+        }
+
+        async fn fallocate(
+            &self,
+            _path: &str,
+            _mode: agentfs_sdk::FallocMode,
+            _offset: u64,
+            _len: u64,
+        ) -> std::result::Result<(), agentfs_sdk::error::Error> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads_are_not_serialized() {
+        let concurrent_reads = Arc::new(AtomicUsize::new(0));
+        let max_concurrent_reads = Arc::new(AtomicUsize::new(0));
+        let fs = Arc::new(RwLock::new(SlowFs {
+            concurrent_reads: concurrent_reads.clone(),
+            max_concurrent_reads: max_concurrent_reads.clone(),
+        }) as RwLock<dyn agentfs_sdk::FileSystem + Send>);
+        let adapter = Arc::new(RwLockFsAdapter { inner: fs });
+
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let adapter = adapter.clone();
+                tokio::spawn(async move { adapter.read_file("/f.txt").await })
+            })
+            .collect();
+
+        for reader in readers {
+            reader.await.unwrap().unwrap();
+        }
+
+        // With a shared read lock, several of the 50ms reads should have
+        // been in flight at once; a single global mutex would never let
+        // this rise above 1.
+        assert!(max_concurrent_reads.load(Ordering::SeqCst) > 1);
     }
 }
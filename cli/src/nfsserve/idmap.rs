@@ -0,0 +1,196 @@
+//! Client-to-server uid/gid mapping layer (idmapped-mount style).
+//!
+//! Agents often present credentials in a namespace that differs from the
+//! backing store's ownership. An [`IdMap`] translates ids in both
+//! directions so permission checks and reported attributes stay in a
+//! single consistent id space, without the rest of the server needing to
+//! know mapping is happening at all.
+
+use super::nfs::fattr3;
+use super::rpc::auth_unix;
+
+/// A contiguous range of `count` ids starting at `first_client` that maps
+/// onto `count` ids starting at `first_host`.
+#[derive(Debug, Clone, Copy)]
+pub struct IdRange {
+    pub first_client: u32,
+    pub first_host: u32,
+    pub count: u32,
+}
+
+impl IdRange {
+    fn map_to_host(&self, id: u32) -> Option<u32> {
+        let offset = id.checked_sub(self.first_client)?;
+        (offset < self.count).then(|| self.first_host + offset)
+    }
+
+    fn map_to_client(&self, id: u32) -> Option<u32> {
+        let offset = id.checked_sub(self.first_host)?;
+        (offset < self.count).then(|| self.first_client + offset)
+    }
+}
+
+/// A client<->host id translation table, in the spirit of idmapped mounts.
+///
+/// Explicit overrides are checked before ranges; the first matching range
+/// wins. Ids with no match pass through unchanged, so an empty `IdMap` is
+/// the identity map and behaves byte-for-byte like no mapping at all.
+#[derive(Debug, Clone, Default)]
+pub struct IdMap {
+    ranges: Vec<IdRange>,
+    overrides: Vec<(u32, u32)>,
+}
+
+impl IdMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a range shift mapping `count` client ids starting at
+    /// `first_client` onto host ids starting at `first_host`.
+    pub fn with_range(mut self, first_client: u32, first_host: u32, count: u32) -> Self {
+        self.ranges.push(IdRange {
+            first_client,
+            first_host,
+            count,
+        });
+        self
+    }
+
+    /// Add an explicit single-id override, checked before any range.
+    pub fn with_override(mut self, client_id: u32, host_id: u32) -> Self {
+        self.overrides.push((client_id, host_id));
+        self
+    }
+
+    fn to_host(&self, id: u32) -> u32 {
+        for &(client_id, host_id) in &self.overrides {
+            if client_id == id {
+                return host_id;
+            }
+        }
+        for range in &self.ranges {
+            if let Some(mapped) = range.map_to_host(id) {
+                return mapped;
+            }
+        }
+        id
+    }
+
+    fn to_client(&self, id: u32) -> u32 {
+        for &(client_id, host_id) in &self.overrides {
+            if host_id == id {
+                return client_id;
+            }
+        }
+        for range in &self.ranges {
+            if let Some(mapped) = range.map_to_client(id) {
+                return mapped;
+            }
+        }
+        id
+    }
+
+    /// Map an incoming caller's credentials from client id-space to host
+    /// id-space. Apply this before `check_permission`/`compute_access`/
+    /// `is_owner` so ownership comparisons operate in the host's space.
+    pub fn map_cred(&self, auth: &auth_unix) -> auth_unix {
+        auth_unix {
+            stamp: auth.stamp,
+            machinename: auth.machinename.clone(),
+            uid: self.to_host(auth.uid),
+            gid: self.to_host(auth.gid),
+            gids: auth.gids.iter().map(|&g| self.to_host(g)).collect(),
+        }
+    }
+
+    /// Map a file's on-disk (host) owner back into client id-space, for
+    /// reporting in `fattr3.uid`/`fattr3.gid`.
+    pub fn map_attr_owner(&self, attr: &fattr3) -> (u32, u32) {
+        (self.to_client(attr.uid), self.to_client(attr.gid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nfsserve::nfs::{ftype3, nfstime3, specdata3};
+
+    fn make_auth(uid: u32, gid: u32, gids: Vec<u32>) -> auth_unix {
+        auth_unix {
+            stamp: 0,
+            machinename: Vec::new(),
+            uid,
+            gid,
+            gids,
+        }
+    }
+
+    fn make_attr(uid: u32, gid: u32) -> fattr3 {
+        fattr3 {
+            ftype: ftype3::NF3REG,
+            mode: 0o644,
+            nlink: 1,
+            uid,
+            gid,
+            size: 0,
+            used: 0,
+            rdev: specdata3::default(),
+            fsid: 0,
+            fileid: 1,
+            atime: nfstime3::default(),
+            mtime: nfstime3::default(),
+            ctime: nfstime3::default(),
+        }
+    }
+
+    #[test]
+    fn test_identity_map_is_passthrough() {
+        let map = IdMap::new();
+        let auth = make_auth(1000, 1000, vec![2000]);
+        let mapped = map.map_cred(&auth);
+        assert_eq!(mapped.uid, 1000);
+        assert_eq!(mapped.gid, 1000);
+        assert_eq!(mapped.gids, vec![2000]);
+
+        let attr = make_attr(1000, 1000);
+        assert_eq!(map.map_attr_owner(&attr), (1000, 1000));
+    }
+
+    #[test]
+    fn test_range_shift_maps_cred_to_host() {
+        // Client ids 100000..100010 map onto host ids 0..10 (rootless-container style).
+        let map = IdMap::new().with_range(100_000, 0, 10);
+        let auth = make_auth(100_005, 100_001, vec![]);
+        let mapped = map.map_cred(&auth);
+        assert_eq!(mapped.uid, 5);
+        assert_eq!(mapped.gid, 1);
+    }
+
+    #[test]
+    fn test_range_shift_maps_attr_back_to_client() {
+        let map = IdMap::new().with_range(100_000, 0, 10);
+        let attr = make_attr(5, 1);
+        assert_eq!(map.map_attr_owner(&attr), (100_005, 100_001));
+    }
+
+    #[test]
+    fn test_id_outside_range_passes_through() {
+        let map = IdMap::new().with_range(100_000, 0, 10);
+        let auth = make_auth(1000, 1000, vec![]);
+        let mapped = map.map_cred(&auth);
+        assert_eq!(mapped.uid, 1000);
+        assert_eq!(mapped.gid, 1000);
+    }
+
+    #[test]
+    fn test_override_takes_precedence_over_range() {
+        let map = IdMap::new()
+            .with_range(100_000, 0, 10)
+            .with_override(100_005, 999);
+        let auth = make_auth(100_005, 100_000, vec![]);
+        let mapped = map.map_cred(&auth);
+        assert_eq!(mapped.uid, 999);
+        assert_eq!(mapped.gid, 0);
+    }
+}
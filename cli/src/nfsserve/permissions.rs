@@ -3,8 +3,10 @@
 //! This module implements RFC 1813 compliant permission checking using
 //! AUTH_UNIX credentials (uid, gid, auxiliary gids) against file mode bits.
 
+use super::idmap::IdMap;
 use super::nfs::fattr3;
 use super::rpc::auth_unix;
+use super::squash::ExportSquash;
 
 /// Permission bits for Unix file modes
 pub const S_IRUSR: u32 = 0o400; // Owner read
@@ -16,6 +18,7 @@ pub const S_IXGRP: u32 = 0o010; // Group execute
 pub const S_IROTH: u32 = 0o004; // Other read
 pub const S_IWOTH: u32 = 0o002; // Other write
 pub const S_IXOTH: u32 = 0o001; // Other execute
+pub const S_ISVTX: u32 = 0o1000; // Sticky bit
 
 /// NFS ACCESS procedure permission bits (from RFC 1813)
 pub const ACCESS3_READ: u32 = 0x0001;
@@ -33,21 +36,141 @@ pub enum Permission {
     Execute,
 }
 
-/// Check if the given auth credentials have the specified permission on the file.
+/// A single POSIX.1e ACL entry.
 ///
-/// This implements standard Unix permission checking:
-/// 1. Root (uid 0) always has all permissions
-/// 2. If caller's uid matches file owner, check owner bits
-/// 3. If caller's gid or any auxiliary gid matches file group, check group bits
-/// 4. Otherwise check "other" bits
-pub fn check_permission(auth: &auth_unix, attr: &fattr3, perm: Permission) -> bool {
+/// `perm` uses the same low-order rwx encoding as a mode triad (e.g. `0o4`
+/// for read), since an ACL entry grants exactly one owner/group/other-style
+/// triad's worth of permission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AclEntry {
+    pub tag: AclEntryTag,
+    pub perm: u32,
+}
+
+/// The tag identifying what an `AclEntry` applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AclEntryTag {
+    /// The file owner (mirrors the owner mode triad).
+    UserObj,
+    /// A specific named user, by uid.
+    User(u32),
+    /// The owning group (mirrors the group mode triad).
+    GroupObj,
+    /// A specific named group, by gid.
+    Group(u32),
+    /// The mask entry, capping all group-class permissions.
+    Mask,
+    /// Everyone else (mirrors the other mode triad).
+    Other,
+}
+
+/// A POSIX.1e access control list attached to a file.
+#[derive(Debug, Clone, Default)]
+pub struct PosixAcl {
+    pub entries: Vec<AclEntry>,
+}
+
+impl PosixAcl {
+    fn find(&self, tag: AclEntryTag) -> Option<&AclEntry> {
+        self.entries.iter().find(|e| e.tag == tag)
+    }
+
+    fn mask(&self) -> Option<u32> {
+        self.find(AclEntryTag::Mask).map(|e| e.perm)
+    }
+
+    /// Resolve the effective rwx bits granted to `auth` by this ACL.
+    ///
+    /// Implements the standard POSIX.1e resolution order: owner entry
+    /// (unmasked), else a matching named-user entry (masked), else the
+    /// union of the owning-group entry and any matching named-group
+    /// entries (masked), else the other entry.
+    fn resolve(&self, auth: &auth_unix, file_uid: u32, file_gid: u32) -> u32 {
+        if auth.uid == file_uid {
+            return self.find(AclEntryTag::UserObj).map(|e| e.perm).unwrap_or(0);
+        }
+
+        if let Some(entry) = self.find(AclEntryTag::User(auth.uid)) {
+            return entry.perm & self.mask().unwrap_or(u32::MAX);
+        }
+
+        let mut group_perm = None;
+        if is_in_group(auth, file_gid) {
+            group_perm = Some(self.find(AclEntryTag::GroupObj).map(|e| e.perm).unwrap_or(0));
+        }
+        for gid in std::iter::once(auth.gid).chain(auth.gids.iter().copied()) {
+            if let Some(entry) = self.find(AclEntryTag::Group(gid)) {
+                group_perm = Some(group_perm.unwrap_or(0) | entry.perm);
+            }
+        }
+        if let Some(perm) = group_perm {
+            return perm & self.mask().unwrap_or(u32::MAX);
+        }
+
+        self.find(AclEntryTag::Other).map(|e| e.perm).unwrap_or(0)
+    }
+}
+
+/// Apply an export's squash policy, then a client<->host id mapping, to
+/// `auth`, if either was given.
+///
+/// Every entry point below that compares `auth.uid`/`auth.gid` against file
+/// attributes routes through here first, so neither can be bypassed by
+/// calling deeper into this module with the raw credential. Squashing runs
+/// first since it acts on the identity the client actually asserted (e.g.
+/// "is this caller claiming to be root"); the result is then translated
+/// into the host id space `attr`'s `uid`/`gid` already live in, the same
+/// space `is_owner` and the mode-triad checks compare against. `None` for
+/// either behaves like the identity transform.
+fn effective_auth(
+    auth: &auth_unix,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> auth_unix {
+    let auth = squash.copied().unwrap_or_default().squash(auth);
+    match idmap {
+        Some(idmap) => idmap.map_cred(&auth),
+        None => auth,
+    }
+}
+
+/// The result of a permission check augmented with whether the grant
+/// required superuser privilege.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessDecision {
+    pub granted: bool,
+    pub privileged: bool,
+}
+
+/// Check if the given auth credentials have the specified permission on the
+/// file, ignoring the `uid == 0` root shortcut entirely.
+///
+/// This implements standard Unix DAC permission checking:
+/// 1. If caller's uid matches file owner, check owner bits
+/// 2. If caller's gid or any auxiliary gid matches file group, check group bits
+/// 3. Otherwise check "other" bits
+///
+/// If `acl` is `Some`, it is consulted instead of the owner/group/other mode
+/// triads (per POSIX.1e resolution order), letting exports with richer ACLs
+/// grant access the bare mode bits can't express.
+fn check_permission_dac(
+    auth: &auth_unix,
+    attr: &fattr3,
+    perm: Permission,
+    acl: Option<&PosixAcl>,
+) -> bool {
     let mode = attr.mode;
     let file_uid = attr.uid;
     let file_gid = attr.gid;
 
-    // Root always has access
-    if auth.uid == 0 {
-        return true;
+    let bit = match perm {
+        Permission::Read => 0o4,
+        Permission::Write => 0o2,
+        Permission::Execute => 0o1,
+    };
+
+    if let Some(acl) = acl {
+        return (acl.resolve(auth, file_uid, file_gid) & bit) != 0;
     }
 
     // Determine which permission bits to check based on user/group/other
@@ -70,6 +193,63 @@ pub fn check_permission(auth: &auth_unix, attr: &fattr3, perm: Permission) -> bo
     }
 }
 
+/// Check permission and report whether superuser privilege was required to
+/// grant it.
+///
+/// Performs the full DAC check against owner/group/other bits (or the ACL,
+/// when provided) ignoring the `uid == 0` shortcut, and only falls back to
+/// the root override if that fails. `privileged` is true if, and only if,
+/// the grant came solely from the caller being root — useful for auditing
+/// exactly which operations succeeded only because of root.
+///
+/// `squash` and `idmap`, when given, are applied to `auth` before the check
+/// (see [`effective_auth`]), so an export's root_squash/all_squash policy
+/// and any client<->host id mapping are both honored rather than left for
+/// the caller to remember to apply.
+pub fn check_permission_ex(
+    auth: &auth_unix,
+    attr: &fattr3,
+    perm: Permission,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> AccessDecision {
+    let auth = effective_auth(auth, squash, idmap);
+
+    if check_permission_dac(&auth, attr, perm, acl) {
+        return AccessDecision {
+            granted: true,
+            privileged: false,
+        };
+    }
+
+    if auth.uid == 0 {
+        return AccessDecision {
+            granted: true,
+            privileged: true,
+        };
+    }
+
+    AccessDecision {
+        granted: false,
+        privileged: false,
+    }
+}
+
+/// Check if the given auth credentials have the specified permission on the
+/// file. A thin wrapper over [`check_permission_ex`] for callers that don't
+/// need to know whether the grant was privileged.
+pub fn check_permission(
+    auth: &auth_unix,
+    attr: &fattr3,
+    perm: Permission,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    check_permission_ex(auth, attr, perm, acl, squash, idmap).granted
+}
+
 /// Check if auth credentials are in the specified group.
 /// Returns true if the primary gid matches or if gid is in auxiliary groups.
 fn is_in_group(auth: &auth_unix, gid: u32) -> bool {
@@ -80,18 +260,36 @@ fn is_in_group(auth: &auth_unix, gid: u32) -> bool {
 }
 
 /// Check if the caller can read the file.
-pub fn can_read(auth: &auth_unix, attr: &fattr3) -> bool {
-    check_permission(auth, attr, Permission::Read)
+pub fn can_read(
+    auth: &auth_unix,
+    attr: &fattr3,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    check_permission(auth, attr, Permission::Read, acl, squash, idmap)
 }
 
 /// Check if the caller can write to the file.
-pub fn can_write(auth: &auth_unix, attr: &fattr3) -> bool {
-    check_permission(auth, attr, Permission::Write)
+pub fn can_write(
+    auth: &auth_unix,
+    attr: &fattr3,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    check_permission(auth, attr, Permission::Write, acl, squash, idmap)
 }
 
 /// Check if the caller can execute the file or search the directory.
-pub fn can_execute(auth: &auth_unix, attr: &fattr3) -> bool {
-    check_permission(auth, attr, Permission::Execute)
+pub fn can_execute(
+    auth: &auth_unix,
+    attr: &fattr3,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    check_permission(auth, attr, Permission::Execute, acl, squash, idmap)
 }
 
 /// Compute the ACCESS3 result bitmask for the given auth and file attributes.
@@ -103,47 +301,69 @@ pub fn can_execute(auth: &auth_unix, attr: &fattr3) -> bool {
 /// - ACCESS3_EXTEND: add new data or directory entries
 /// - ACCESS3_DELETE: remove directory entries (checked against parent directory)
 /// - ACCESS3_EXECUTE: execute files (execute permission on files)
-pub fn compute_access(auth: &auth_unix, attr: &fattr3, requested: u32) -> u32 {
+///
+/// When `acl` is `Some`, every permission bit above is resolved against the
+/// POSIX.1e ACL instead of the mode triads; see [`check_permission`].
+///
+/// `delete_target`, when known, is the attributes of the specific entry the
+/// caller is probing deletability for; it lets DELETE reflect sticky-bit
+/// restrictions (see [`can_delete`]) rather than plain directory write
+/// permission. Pass `None` when no specific entry is in question.
+///
+/// `squash` and `idmap`, when given, are applied to `auth` before every
+/// check below (see [`effective_auth`]).
+pub fn compute_access(
+    auth: &auth_unix,
+    attr: &fattr3,
+    requested: u32,
+    acl: Option<&PosixAcl>,
+    delete_target: Option<&fattr3>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> u32 {
     let mut result = 0u32;
     let is_dir = matches!(attr.ftype, super::nfs::ftype3::NF3DIR);
 
     // ACCESS3_READ - read file data or directory contents
-    if (requested & ACCESS3_READ) != 0 && can_read(auth, attr) {
+    if (requested & ACCESS3_READ) != 0 && can_read(auth, attr, acl, squash, idmap) {
         result |= ACCESS3_READ;
     }
 
     // ACCESS3_LOOKUP - search directory (execute permission on directories)
     if (requested & ACCESS3_LOOKUP) != 0 {
-        if is_dir && can_execute(auth, attr) {
+        if is_dir && can_execute(auth, attr, acl, squash, idmap) {
             result |= ACCESS3_LOOKUP;
         }
     }
 
     // ACCESS3_MODIFY - alter existing data (write permission)
-    if (requested & ACCESS3_MODIFY) != 0 && can_write(auth, attr) {
+    if (requested & ACCESS3_MODIFY) != 0 && can_write(auth, attr, acl, squash, idmap) {
         result |= ACCESS3_MODIFY;
     }
 
     // ACCESS3_EXTEND - add new data (write permission)
-    if (requested & ACCESS3_EXTEND) != 0 && can_write(auth, attr) {
+    if (requested & ACCESS3_EXTEND) != 0 && can_write(auth, attr, acl, squash, idmap) {
         result |= ACCESS3_EXTEND;
     }
 
     // ACCESS3_DELETE - for non-directory files, always 0 (per RFC 1813)
-    // For directories, this would need to check parent directory permissions
-    // which is handled at the operation level, not here
+    // For directories, reflect the sticky-bit-aware `can_delete` rules when
+    // a specific target is known, falling back to plain directory write
+    // permission otherwise.
     if (requested & ACCESS3_DELETE) != 0 {
-        // DELETE permission is checked at operation time against the parent directory
-        // For the ACCESS procedure, we return 0 for files (per UNIX semantics)
-        // and the directory's write permission for directories
-        if is_dir && can_write(auth, attr) {
+        let allowed = is_dir
+            && match delete_target {
+                Some(target) => can_delete(auth, attr, target, acl, squash, idmap),
+                None => can_write(auth, attr, acl, squash, idmap),
+            };
+        if allowed {
             result |= ACCESS3_DELETE;
         }
     }
 
     // ACCESS3_EXECUTE - execute files (not directories)
     if (requested & ACCESS3_EXECUTE) != 0 {
-        if !is_dir && can_execute(auth, attr) {
+        if !is_dir && can_execute(auth, attr, acl, squash, idmap) {
             result |= ACCESS3_EXECUTE;
         }
     }
@@ -153,16 +373,62 @@ pub fn compute_access(auth: &auth_unix, attr: &fattr3, requested: u32) -> u32 {
 
 /// Check if caller has permission to modify a directory (create, remove, rename entries).
 /// This requires write AND execute permission on the directory.
-pub fn can_modify_directory(auth: &auth_unix, dir_attr: &fattr3) -> bool {
-    can_write(auth, dir_attr) && can_execute(auth, dir_attr)
+pub fn can_modify_directory(
+    auth: &auth_unix,
+    dir_attr: &fattr3,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    can_write(auth, dir_attr, acl, squash, idmap) && can_execute(auth, dir_attr, acl, squash, idmap)
 }
 
 /// Check if caller is the owner of the file (or root).
 /// Used for operations like chmod that require ownership.
-pub fn is_owner(auth: &auth_unix, attr: &fattr3) -> bool {
+///
+/// `squash` and `idmap`, when given, are applied to `auth` first (see
+/// [`effective_auth`]), so a squashed anonymous caller can't be mistaken for
+/// the file's owner, and ownership is compared in the same host id space
+/// `attr.uid` lives in.
+pub fn is_owner(
+    auth: &auth_unix,
+    attr: &fattr3,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    let auth = effective_auth(auth, squash, idmap);
     auth.uid == 0 || auth.uid == attr.uid
 }
 
+/// Check whether `auth` may unlink `target` out of `parent_dir`.
+///
+/// Deletion always requires write and execute permission on the parent
+/// directory. If the parent has the sticky bit (`S_ISVTX`) set, that's not
+/// enough: only the owner of `target`, the owner of `parent_dir`, or root
+/// may remove the entry.
+///
+/// `squash` and `idmap`, when given, are applied to `auth` before every
+/// comparison below (see [`effective_auth`]).
+pub fn can_delete(
+    auth: &auth_unix,
+    parent_dir: &fattr3,
+    target: &fattr3,
+    acl: Option<&PosixAcl>,
+    squash: Option<&ExportSquash>,
+    idmap: Option<&IdMap>,
+) -> bool {
+    if !can_modify_directory(auth, parent_dir, acl, squash, idmap) {
+        return false;
+    }
+
+    if (parent_dir.mode & S_ISVTX) == 0 {
+        return true;
+    }
+
+    let auth = effective_auth(auth, squash, idmap);
+    auth.uid == 0 || auth.uid == target.uid || auth.uid == parent_dir.uid
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -200,9 +466,9 @@ mod tests {
     fn test_root_always_allowed() {
         let auth = make_auth(0, 0, vec![]);
         let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
-        assert!(can_write(&auth, &attr));
-        assert!(can_execute(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
+        assert!(can_write(&auth, &attr, None, None, None));
+        assert!(can_execute(&auth, &attr, None, None, None));
     }
 
     #[test]
@@ -211,21 +477,21 @@ mod tests {
 
         // Owner read only
         let attr = make_attr(0o400, 1000, 2000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
-        assert!(!can_write(&auth, &attr));
-        assert!(!can_execute(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
+        assert!(!can_write(&auth, &attr, None, None, None));
+        assert!(!can_execute(&auth, &attr, None, None, None));
 
         // Owner write only
         let attr = make_attr(0o200, 1000, 2000, ftype3::NF3REG);
-        assert!(!can_read(&auth, &attr));
-        assert!(can_write(&auth, &attr));
-        assert!(!can_execute(&auth, &attr));
+        assert!(!can_read(&auth, &attr, None, None, None));
+        assert!(can_write(&auth, &attr, None, None, None));
+        assert!(!can_execute(&auth, &attr, None, None, None));
 
         // Owner execute only
         let attr = make_attr(0o100, 1000, 2000, ftype3::NF3REG);
-        assert!(!can_read(&auth, &attr));
-        assert!(!can_write(&auth, &attr));
-        assert!(can_execute(&auth, &attr));
+        assert!(!can_read(&auth, &attr, None, None, None));
+        assert!(!can_write(&auth, &attr, None, None, None));
+        assert!(can_execute(&auth, &attr, None, None, None));
     }
 
     #[test]
@@ -234,13 +500,13 @@ mod tests {
 
         // Group read only
         let attr = make_attr(0o040, 3000, 2000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
-        assert!(!can_write(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
+        assert!(!can_write(&auth, &attr, None, None, None));
 
         // Group write only
         let attr = make_attr(0o020, 3000, 2000, ftype3::NF3REG);
-        assert!(!can_read(&auth, &attr));
-        assert!(can_write(&auth, &attr));
+        assert!(!can_read(&auth, &attr, None, None, None));
+        assert!(can_write(&auth, &attr, None, None, None));
     }
 
     #[test]
@@ -249,10 +515,10 @@ mod tests {
 
         // User not owner, but in aux group
         let attr = make_attr(0o040, 9999, 2000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
 
         let attr = make_attr(0o040, 9999, 3000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
     }
 
     #[test]
@@ -261,13 +527,13 @@ mod tests {
 
         // Other read only
         let attr = make_attr(0o004, 2000, 2000, ftype3::NF3REG);
-        assert!(can_read(&auth, &attr));
-        assert!(!can_write(&auth, &attr));
+        assert!(can_read(&auth, &attr, None, None, None));
+        assert!(!can_write(&auth, &attr, None, None, None));
 
         // Other write only
         let attr = make_attr(0o002, 2000, 2000, ftype3::NF3REG);
-        assert!(!can_read(&auth, &attr));
-        assert!(can_write(&auth, &attr));
+        assert!(!can_read(&auth, &attr, None, None, None));
+        assert!(can_write(&auth, &attr, None, None, None));
     }
 
     #[test]
@@ -276,7 +542,7 @@ mod tests {
 
         // Regular file with rwx for owner
         let attr = make_attr(0o700, 1000, 1000, ftype3::NF3REG);
-        let access = compute_access(&auth, &attr, 0x3f);
+        let access = compute_access(&auth, &attr, 0x3f, None, None, None, None);
         assert!((access & ACCESS3_READ) != 0);
         assert!((access & ACCESS3_MODIFY) != 0);
         assert!((access & ACCESS3_EXTEND) != 0);
@@ -286,7 +552,7 @@ mod tests {
 
         // Directory with rwx for owner
         let attr = make_attr(0o700, 1000, 1000, ftype3::NF3DIR);
-        let access = compute_access(&auth, &attr, 0x3f);
+        let access = compute_access(&auth, &attr, 0x3f, None, None, None, None);
         assert!((access & ACCESS3_READ) != 0);
         assert!((access & ACCESS3_LOOKUP) != 0);
         assert!((access & ACCESS3_MODIFY) != 0);
@@ -295,4 +561,338 @@ mod tests {
         // EXECUTE only for files
         assert!((access & ACCESS3_EXECUTE) == 0);
     }
+
+    #[test]
+    fn test_acl_owner_entry_unmasked() {
+        // Owner entry grants read+write even though mask strips it to read-only.
+        let acl = PosixAcl {
+            entries: vec![
+                AclEntry {
+                    tag: AclEntryTag::UserObj,
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Mask,
+                    perm: 0o4,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Other,
+                    perm: 0o0,
+                },
+            ],
+        };
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o000, 1000, 2000, ftype3::NF3REG);
+        assert!(can_read(&auth, &attr, Some(&acl), None, None));
+        assert!(can_write(&auth, &attr, Some(&acl), None, None));
+    }
+
+    #[test]
+    fn test_acl_named_user_masked() {
+        let acl = PosixAcl {
+            entries: vec![
+                AclEntry {
+                    tag: AclEntryTag::UserObj,
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::User(2000),
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Mask,
+                    perm: 0o4,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Other,
+                    perm: 0o0,
+                },
+            ],
+        };
+        let auth = make_auth(2000, 3000, vec![]);
+        let attr = make_attr(0o000, 1000, 3000, ftype3::NF3REG);
+        // Named user entry grants rw, but the mask caps it to read-only.
+        assert!(can_read(&auth, &attr, Some(&acl), None, None));
+        assert!(!can_write(&auth, &attr, Some(&acl), None, None));
+    }
+
+    #[test]
+    fn test_acl_named_group_union_masked() {
+        let acl = PosixAcl {
+            entries: vec![
+                AclEntry {
+                    tag: AclEntryTag::UserObj,
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::GroupObj,
+                    perm: 0o0,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Group(4000),
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Mask,
+                    perm: 0o4,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Other,
+                    perm: 0o0,
+                },
+            ],
+        };
+        let auth = make_auth(2000, 3000, vec![4000]);
+        let attr = make_attr(0o000, 1000, 3000, ftype3::NF3REG);
+        // Owning group grants nothing, but the named group via aux gids grants
+        // read (after masking away the write bit).
+        assert!(can_read(&auth, &attr, Some(&acl), None, None));
+        assert!(!can_write(&auth, &attr, Some(&acl), None, None));
+    }
+
+    #[test]
+    fn test_acl_falls_back_to_other() {
+        let acl = PosixAcl {
+            entries: vec![
+                AclEntry {
+                    tag: AclEntryTag::UserObj,
+                    perm: 0o6,
+                },
+                AclEntry {
+                    tag: AclEntryTag::GroupObj,
+                    perm: 0o0,
+                },
+                AclEntry {
+                    tag: AclEntryTag::Other,
+                    perm: 0o4,
+                },
+            ],
+        };
+        let auth = make_auth(2000, 3000, vec![]);
+        let attr = make_attr(0o000, 1000, 4000, ftype3::NF3REG);
+        assert!(can_read(&auth, &attr, Some(&acl), None, None));
+        assert!(!can_write(&auth, &attr, Some(&acl), None, None));
+    }
+
+    #[test]
+    fn test_acl_root_bypasses_acl() {
+        let acl = PosixAcl {
+            entries: vec![AclEntry {
+                tag: AclEntryTag::Other,
+                perm: 0o0,
+            }],
+        };
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
+        assert!(can_read(&auth, &attr, Some(&acl), None, None));
+        assert!(can_write(&auth, &attr, Some(&acl), None, None));
+        assert!(can_execute(&auth, &attr, Some(&acl), None, None));
+    }
+
+    #[test]
+    fn test_can_delete_without_sticky_bit_needs_only_dir_write() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let parent_dir = make_attr(0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 3000, 3000, ftype3::NF3REG);
+        assert!(can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_can_delete_sticky_bit_allows_target_owner() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let parent_dir = make_attr(S_ISVTX | 0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 1000, 1000, ftype3::NF3REG);
+        assert!(can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_can_delete_sticky_bit_allows_dir_owner() {
+        let auth = make_auth(2000, 2000, vec![]);
+        let parent_dir = make_attr(S_ISVTX | 0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 3000, 3000, ftype3::NF3REG);
+        assert!(can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_can_delete_sticky_bit_denies_other_users() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let parent_dir = make_attr(S_ISVTX | 0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 3000, 3000, ftype3::NF3REG);
+        assert!(!can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_can_delete_sticky_bit_allows_root() {
+        let auth = make_auth(0, 0, vec![]);
+        let parent_dir = make_attr(S_ISVTX | 0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 3000, 3000, ftype3::NF3REG);
+        assert!(can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_can_delete_requires_dir_write_permission() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let parent_dir = make_attr(0o555, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 1000, 1000, ftype3::NF3REG);
+        assert!(!can_delete(&auth, &parent_dir, &target, None, None, None));
+    }
+
+    #[test]
+    fn test_compute_access_delete_respects_sticky_bit() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let parent_dir = make_attr(S_ISVTX | 0o777, 2000, 2000, ftype3::NF3DIR);
+        let target = make_attr(0o644, 3000, 3000, ftype3::NF3REG);
+
+        let access = compute_access(
+            &auth,
+            &parent_dir,
+            ACCESS3_DELETE,
+            None,
+            Some(&target),
+            None,
+            None,
+        );
+        assert_eq!(access & ACCESS3_DELETE, 0);
+
+        let owned_target = make_attr(0o644, 1000, 1000, ftype3::NF3REG);
+        let access = compute_access(
+            &auth,
+            &parent_dir,
+            ACCESS3_DELETE,
+            None,
+            Some(&owned_target),
+            None,
+            None,
+        );
+        assert_eq!(access & ACCESS3_DELETE, ACCESS3_DELETE);
+    }
+
+    #[test]
+    fn test_check_permission_ex_unprivileged_grant() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o400, 1000, 1000, ftype3::NF3REG);
+        let decision = check_permission_ex(&auth, &attr, Permission::Read, None, None, None);
+        assert!(decision.granted);
+        assert!(!decision.privileged);
+    }
+
+    #[test]
+    fn test_check_permission_ex_privileged_grant() {
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
+        let decision = check_permission_ex(&auth, &attr, Permission::Write, None, None, None);
+        assert!(decision.granted);
+        assert!(decision.privileged);
+    }
+
+    #[test]
+    fn test_check_permission_ex_root_with_dac_grant_is_not_privileged() {
+        // Root also happens to be the owner with the bit set via mode - the
+        // grant should be attributed to DAC, not to the root override.
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o400, 0, 0, ftype3::NF3REG);
+        let decision = check_permission_ex(&auth, &attr, Permission::Read, None, None, None);
+        assert!(decision.granted);
+        assert!(!decision.privileged);
+    }
+
+    #[test]
+    fn test_check_permission_ex_denied() {
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o000, 2000, 2000, ftype3::NF3REG);
+        let decision = check_permission_ex(&auth, &attr, Permission::Read, None, None, None);
+        assert!(!decision.granted);
+        assert!(!decision.privileged);
+    }
+
+    #[test]
+    fn test_check_permission_thin_wrapper_matches_ex() {
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
+        assert_eq!(
+            check_permission(&auth, &attr, Permission::Execute, None, None, None),
+            check_permission_ex(&auth, &attr, Permission::Execute, None, None, None).granted
+        );
+    }
+
+    #[test]
+    fn test_root_squash_strips_root_override_before_dac_check() {
+        // Root's DAC check would fail against this mode, so without the
+        // root override it has no grant left - root_squash must make that
+        // count, not just rewrite uid/gid for logging purposes.
+        use crate::nfsserve::squash::{ExportSquash, SquashMode};
+
+        let squash = ExportSquash::new(SquashMode::RootSquash);
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
+        assert!(!can_read(&auth, &attr, None, Some(&squash), None));
+    }
+
+    #[test]
+    fn test_all_squash_denies_real_owner_access() {
+        // An all_squash export must check the anonymous identity, not the
+        // client's real (and here, file-owning) uid.
+        use crate::nfsserve::squash::{ExportSquash, SquashMode};
+
+        let squash = ExportSquash::new(SquashMode::AllSquash);
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o700, 1000, 1000, ftype3::NF3REG);
+        assert!(!can_read(&auth, &attr, None, Some(&squash), None));
+        assert!(!is_owner(&auth, &attr, Some(&squash), None));
+    }
+
+    #[test]
+    fn test_no_squash_matches_unsquashed_behavior() {
+        use crate::nfsserve::squash::ExportSquash;
+
+        let squash = ExportSquash::default();
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o400, 1000, 1000, ftype3::NF3REG);
+        assert_eq!(
+            can_read(&auth, &attr, None, Some(&squash), None),
+            can_read(&auth, &attr, None, None, None)
+        );
+    }
+
+    #[test]
+    fn test_idmap_translates_client_cred_before_ownership_check() {
+        // File is owned by host uid 5; a client presenting the
+        // corresponding client-space id (100_005) must be recognized as
+        // the owner only once mapped into host space.
+        use crate::nfsserve::idmap::IdMap;
+
+        let idmap = IdMap::new().with_range(100_000, 0, 10);
+        let auth = make_auth(100_005, 100_001, vec![]);
+        let attr = make_attr(0o600, 5, 1, ftype3::NF3REG);
+        assert!(!is_owner(&auth, &attr, None, None));
+        assert!(is_owner(&auth, &attr, None, Some(&idmap)));
+    }
+
+    #[test]
+    fn test_idmap_and_squash_compose_squash_first() {
+        // root_squash is based on the client's asserted uid (0), which must
+        // be evaluated before any id mapping - squash should still fire
+        // even though uid 0 maps onto a different host id.
+        use crate::nfsserve::idmap::IdMap;
+        use crate::nfsserve::squash::{ExportSquash, SquashMode};
+
+        let squash = ExportSquash::new(SquashMode::RootSquash);
+        let idmap = IdMap::new().with_range(0, 100_000, 10);
+        let auth = make_auth(0, 0, vec![]);
+        let attr = make_attr(0o000, 1000, 1000, ftype3::NF3REG);
+        assert!(!can_read(&auth, &attr, None, Some(&squash), Some(&idmap)));
+    }
+
+    #[test]
+    fn test_no_idmap_matches_unmapped_behavior() {
+        use crate::nfsserve::idmap::IdMap;
+
+        let idmap = IdMap::new();
+        let auth = make_auth(1000, 1000, vec![]);
+        let attr = make_attr(0o400, 1000, 1000, ftype3::NF3REG);
+        assert_eq!(
+            can_read(&auth, &attr, None, None, Some(&idmap)),
+            can_read(&auth, &attr, None, None, None)
+        );
+    }
 }
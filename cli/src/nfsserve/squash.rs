@@ -0,0 +1,160 @@
+//! Export-level credential squashing (root_squash / all_squash / anon ids).
+//!
+//! Mirrors classic NFS export options: rewrites `auth_unix` credentials
+//! before they reach any of [`super::permissions`]'s checks, so a squashed
+//! export can't be used to gain real root access over the wire.
+
+use super::rpc::auth_unix;
+
+/// Default anonymous uid/gid, matching the conventional `nobody` account.
+pub const DEFAULT_ANON_UID: u32 = 65534;
+pub const DEFAULT_ANON_GID: u32 = 65534;
+
+/// Squashing mode applied to incoming credentials for an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SquashMode {
+    /// No credential rewriting; callers are trusted as presented.
+    #[default]
+    NoSquash,
+    /// Remap uid/gid 0 (root) to the configured anonymous ids.
+    RootSquash,
+    /// Remap every caller to the configured anonymous ids.
+    AllSquash,
+}
+
+/// Per-export credential squashing configuration.
+#[derive(Debug, Clone, Copy)]
+pub struct ExportSquash {
+    pub mode: SquashMode,
+    pub anon_uid: u32,
+    pub anon_gid: u32,
+}
+
+impl Default for ExportSquash {
+    fn default() -> Self {
+        Self {
+            mode: SquashMode::NoSquash,
+            anon_uid: DEFAULT_ANON_UID,
+            anon_gid: DEFAULT_ANON_GID,
+        }
+    }
+}
+
+impl ExportSquash {
+    /// Create a squash config for `mode` using the default anonymous ids.
+    pub fn new(mode: SquashMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+
+    fn anon_cred(&self, stamp: u32) -> auth_unix {
+        auth_unix {
+            stamp,
+            machinename: Vec::new(),
+            uid: self.anon_uid,
+            gid: self.anon_gid,
+            gids: Vec::new(),
+        }
+    }
+
+    /// Rewrite `auth` according to this export's squash mode.
+    ///
+    /// Call this before passing credentials into `check_permission`,
+    /// `compute_access`, or `is_owner` so every permission decision is made
+    /// against the squashed identity rather than the one the client sent.
+    pub fn squash(&self, auth: &auth_unix) -> auth_unix {
+        match self.mode {
+            SquashMode::NoSquash => auth_unix {
+                stamp: auth.stamp,
+                machinename: auth.machinename.clone(),
+                uid: auth.uid,
+                gid: auth.gid,
+                gids: auth.gids.clone(),
+            },
+            SquashMode::AllSquash => self.anon_cred(auth.stamp),
+            SquashMode::RootSquash => {
+                if auth.uid == 0 || auth.gid == 0 {
+                    self.anon_cred(auth.stamp)
+                } else {
+                    auth_unix {
+                        stamp: auth.stamp,
+                        machinename: auth.machinename.clone(),
+                        uid: auth.uid,
+                        gid: auth.gid,
+                        gids: auth.gids.clone(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_auth(uid: u32, gid: u32, gids: Vec<u32>) -> auth_unix {
+        auth_unix {
+            stamp: 42,
+            machinename: b"client".to_vec(),
+            uid,
+            gid,
+            gids,
+        }
+    }
+
+    #[test]
+    fn test_no_squash_passes_through() {
+        let squash = ExportSquash::new(SquashMode::NoSquash);
+        let auth = make_auth(0, 0, vec![1, 2]);
+        let squashed = squash.squash(&auth);
+        assert_eq!(squashed.uid, 0);
+        assert_eq!(squashed.gid, 0);
+        assert_eq!(squashed.gids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_root_squash_remaps_root() {
+        let squash = ExportSquash::new(SquashMode::RootSquash);
+        let auth = make_auth(0, 0, vec![1, 2]);
+        let squashed = squash.squash(&auth);
+        assert_eq!(squashed.uid, DEFAULT_ANON_UID);
+        assert_eq!(squashed.gid, DEFAULT_ANON_GID);
+        assert!(squashed.gids.is_empty());
+    }
+
+    #[test]
+    fn test_root_squash_leaves_non_root_alone() {
+        let squash = ExportSquash::new(SquashMode::RootSquash);
+        let auth = make_auth(1000, 1000, vec![2000]);
+        let squashed = squash.squash(&auth);
+        assert_eq!(squashed.uid, 1000);
+        assert_eq!(squashed.gid, 1000);
+        assert_eq!(squashed.gids, vec![2000]);
+    }
+
+    #[test]
+    fn test_all_squash_remaps_every_caller() {
+        let squash = ExportSquash::new(SquashMode::AllSquash);
+        let auth = make_auth(1000, 1000, vec![2000]);
+        let squashed = squash.squash(&auth);
+        assert_eq!(squashed.uid, DEFAULT_ANON_UID);
+        assert_eq!(squashed.gid, DEFAULT_ANON_GID);
+        assert!(squashed.gids.is_empty());
+    }
+
+    #[test]
+    fn test_custom_anon_ids() {
+        let squash = ExportSquash {
+            mode: SquashMode::AllSquash,
+            anon_uid: 100,
+            anon_gid: 200,
+        };
+        let auth = make_auth(1000, 1000, vec![]);
+        let squashed = squash.squash(&auth);
+        assert_eq!(squashed.uid, 100);
+        assert_eq!(squashed.gid, 200);
+    }
+}
@@ -0,0 +1,1406 @@
+//! Pluggable storage backend for [`Filesystem`](crate::Filesystem).
+//!
+//! `Filesystem` only knows about path strings, symlink-following, and the
+//! path resolution cache; every on-disk detail of how inodes, directory
+//! entries, and file data are actually stored lives behind the
+//! [`VfsBackend`] trait. The only implementor today is [`SqliteBackend`],
+//! but a test suite (or an ephemeral agent scratch space) can plug in an
+//! in-memory backend without touching any path-resolution logic, and a
+//! remote/object-store backend could be added the same way.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use turso::{Connection, Value};
+
+use crate::{DedupStats, FsckReport};
+
+const DEFAULT_CHUNK_SIZE: usize = 4096;
+const DEFAULT_COMPRESSION_CODEC: CompressionCodec = CompressionCodec::Zstd;
+const DEFAULT_DIR_MODE: u32 = 0o040000 | 0o755; // S_IFDIR, rwxr-xr-x
+const ROOT_INO: i64 = 1;
+const S_IFMT: u32 = 0o170000;
+const S_IFREG: u32 = 0o100000;
+
+/// Which compression, if any, new blocks are stored with. Each block's own
+/// choice is recorded alongside it in `fs_block.codec`, so changing this
+/// setting only affects blocks written from then on — existing ones stay
+/// readable under whatever codec they were written with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompressionCodec {
+    /// Stored as-is. Used both when compression is disabled and per-block,
+    /// whenever compressing wouldn't have actually saved space.
+    None,
+    Zstd,
+}
+
+impl CompressionCodec {
+    fn as_str(&self) -> &'static str {
+        match self {
+            CompressionCodec::None => "none",
+            CompressionCodec::Zstd => "zstd",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "zstd" => CompressionCodec::Zstd,
+            _ => CompressionCodec::None,
+        }
+    }
+
+    /// The tag stored in `fs_block.codec` for a block written under this
+    /// codec (distinct from [`Self`] itself: an incompressible block is
+    /// always tagged `0`/none regardless of the backend's configured codec).
+    fn tag(&self) -> i64 {
+        match self {
+            CompressionCodec::None => 0,
+            CompressionCodec::Zstd => 1,
+        }
+    }
+}
+
+/// Compress `data` under `codec`, returning the bytes to store and the tag
+/// to record for them. Falls back to storing raw whenever the "compressed"
+/// result isn't actually smaller.
+fn compress_block(data: &[u8], codec: CompressionCodec) -> Result<(Vec<u8>, i64)> {
+    let compressed = match codec {
+        CompressionCodec::None => None,
+        CompressionCodec::Zstd => Some(zstd::stream::encode_all(data, 0)?),
+    };
+
+    match compressed {
+        Some(compressed) if compressed.len() < data.len() => {
+            Ok((compressed, CompressionCodec::Zstd.tag()))
+        }
+        _ => Ok((data.to_vec(), CompressionCodec::None.tag())),
+    }
+}
+
+/// Reverse [`compress_block`], given the tag stored alongside the block.
+fn decompress_block(data: Vec<u8>, codec_tag: i64) -> Result<Vec<u8>> {
+    match codec_tag {
+        0 => Ok(data),
+        1 => Ok(zstd::stream::decode_all(&data[..])?),
+        other => anyhow::bail!("Unknown block codec tag {other}"),
+    }
+}
+
+/// An inode's fixed-size metadata, independent of storage backend.
+pub struct InodeRecord {
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub size: i64,
+    pub atime: i64,
+    pub mtime: i64,
+    pub ctime: i64,
+    /// Device number for character/block device nodes; unused (0) for every
+    /// other file type.
+    pub rdev: u32,
+}
+
+/// Low-level storage primitives a [`crate::Filesystem`] is built on.
+///
+/// Every method here operates on inode numbers and directory-entry names,
+/// never on path strings — path walking, symlink following, and caching
+/// all live in `Filesystem` itself, on top of this trait.
+#[async_trait]
+pub trait VfsBackend: Send + Sync {
+    /// Look up `name` within the directory `parent_ino`.
+    async fn lookup(&self, parent_ino: i64, name: &str) -> Result<Option<i64>>;
+
+    /// Fetch an inode's metadata.
+    async fn get_inode(&self, ino: i64) -> Result<Option<InodeRecord>>;
+
+    /// Create a new inode with the given metadata, returning its ino.
+    /// `rdev` only carries meaning for device-node modes (`S_IFCHR`/
+    /// `S_IFBLK`); pass 0 for every other file type.
+    async fn create_inode(&self, mode: u32, uid: u32, gid: u32, size: i64, rdev: u32)
+        -> Result<i64>;
+
+    /// Update an inode's `size`/`mtime` (used after writes and truncate).
+    async fn update_inode_size(&self, ino: i64, size: i64, mtime: i64) -> Result<()>;
+
+    /// Update an inode's `mode`, bumping `ctime`. The caller is responsible
+    /// for preserving the `S_IFMT` type bits.
+    async fn update_mode(&self, ino: i64, mode: u32, ctime: i64) -> Result<()>;
+
+    /// Update an inode's `uid`/`gid`, bumping `ctime`.
+    async fn update_owner(&self, ino: i64, uid: u32, gid: u32, ctime: i64) -> Result<()>;
+
+    /// Update an inode's `atime`/`mtime`, bumping `ctime`.
+    async fn update_times(&self, ino: i64, atime: i64, mtime: i64, ctime: i64) -> Result<()>;
+
+    /// Delete an inode's metadata row.
+    async fn delete_inode(&self, ino: i64) -> Result<()>;
+
+    /// Create a directory entry `name` under `parent_ino` pointing at `ino`.
+    async fn insert_dentry(&self, parent_ino: i64, name: &str, ino: i64) -> Result<()>;
+
+    /// Remove the directory entry `name` under `parent_ino`.
+    async fn remove_dentry(&self, parent_ino: i64, name: &str) -> Result<()>;
+
+    /// List the names of every entry directly under `ino`, sorted.
+    async fn list_dentries(&self, ino: i64) -> Result<Vec<String>>;
+
+    /// Number of directory entries directly under `ino` (used to check
+    /// whether a directory is empty before removing it).
+    async fn dentry_count(&self, ino: i64) -> Result<i64>;
+
+    /// Number of directory entries that point at `ino` (hard link count).
+    async fn link_count(&self, ino: i64) -> Result<u32>;
+
+    /// Ordered `(chunk_index, hash, byte_length)` layout of a file's chunks.
+    async fn chunk_layout(&self, ino: i64) -> Result<Vec<(i64, Vec<u8>, usize)>>;
+
+    /// The block hash of every chunk belonging to `ino`, in no particular
+    /// order (used to release all of a file's blocks before deleting it).
+    async fn chunk_hashes(&self, ino: i64) -> Result<Vec<Vec<u8>>>;
+
+    /// Fetch a content-addressed block's bytes by hash.
+    async fn block_data(&self, hash: &[u8]) -> Result<Option<Vec<u8>>>;
+
+    /// Concatenate a file's chunks, in order, into one buffer.
+    async fn read_file_data(&self, ino: i64) -> Result<Vec<u8>>;
+
+    /// Store `data` as a content-addressed block, bumping its refcount if
+    /// it already exists, and return its hash.
+    async fn put_block(&self, data: &[u8]) -> Result<[u8; 32]>;
+
+    /// Drop one reference to the block with `hash`, deleting it once its
+    /// refcount reaches zero.
+    async fn release_block(&self, hash: &[u8]) -> Result<()>;
+
+    /// Create a new `(ino, chunk_index) -> hash` entry. Fails if the
+    /// `(ino, chunk_index)` pair already exists.
+    async fn insert_chunk(&self, ino: i64, chunk_index: i64, hash: &[u8]) -> Result<()>;
+
+    /// Repoint an existing `(ino, chunk_index)` entry at a different hash.
+    async fn update_chunk_hash(&self, ino: i64, chunk_index: i64, hash: &[u8]) -> Result<()>;
+
+    /// Drop a single `(ino, chunk_index)` entry.
+    async fn delete_chunk(&self, ino: i64, chunk_index: i64) -> Result<()>;
+
+    /// Drop every chunk entry belonging to `ino`.
+    async fn delete_all_chunks(&self, ino: i64) -> Result<()>;
+
+    /// Read a symlink's target.
+    async fn get_symlink(&self, ino: i64) -> Result<Option<String>>;
+
+    /// Record a symlink's target.
+    async fn set_symlink(&self, ino: i64, target: &str) -> Result<()>;
+
+    /// Delete a symlink's target row, if any.
+    async fn delete_symlink(&self, ino: i64) -> Result<()>;
+
+    /// Logical vs. physical byte counts across the whole block store.
+    async fn dedup_stats(&self) -> Result<DedupStats>;
+
+    /// Set an extended attribute, overwriting any existing value.
+    async fn set_xattr(&self, ino: i64, name: &str, value: &[u8]) -> Result<()>;
+
+    /// Fetch an extended attribute's value, if set.
+    async fn get_xattr(&self, ino: i64, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// List the names of every extended attribute set on `ino`, sorted.
+    async fn list_xattrs(&self, ino: i64) -> Result<Vec<String>>;
+
+    /// Remove an extended attribute. A no-op if it wasn't set.
+    async fn remove_xattr(&self, ino: i64, name: &str) -> Result<()>;
+
+    /// Drop every extended attribute belonging to `ino`.
+    async fn delete_xattrs(&self, ino: i64) -> Result<()>;
+
+    /// Scan for rows left behind by the manual (foreign-key-free) cascade
+    /// deletes elsewhere in this trait, reporting what's wrong. If `repair`
+    /// is true, also delete orphaned rows and recompute mismatched sizes.
+    async fn fsck(&self, repair: bool) -> Result<FsckReport>;
+
+    /// Delete every block no longer referenced by any chunk entry and
+    /// reclaim their on-disk pages, returning the number of physical bytes
+    /// freed. A safety net for blocks a refcount bug or interrupted write
+    /// left behind despite never being pointed at.
+    async fn gc(&self) -> Result<i64>;
+
+    /// Start a transaction. Every write issued through this same connection
+    /// until the matching `commit`/`rollback` is part of it.
+    async fn begin(&self) -> Result<()>;
+
+    /// Commit the current transaction.
+    async fn commit(&self) -> Result<()>;
+
+    /// Abandon the current transaction, discarding every write since `begin`.
+    async fn rollback(&self) -> Result<()>;
+}
+
+/// The [`VfsBackend`] implementation backing [`crate::Filesystem`] today:
+/// every primitive is a direct query against a `turso` (SQLite-compatible)
+/// connection.
+pub struct SqliteBackend {
+    conn: Arc<Connection>,
+    /// The codec new blocks are compressed with, read once at construction
+    /// (mirroring how `Filesystem` caches `chunk_size`) so `put_block`
+    /// doesn't pay a config round trip on every call.
+    codec: CompressionCodec,
+}
+
+impl SqliteBackend {
+    /// Wrap `conn`, creating the schema (tables, indexes, root inode) if it
+    /// doesn't already exist.
+    pub async fn new(conn: Arc<Connection>) -> Result<Self> {
+        Self::initialize_schema(&conn).await?;
+        let codec = Self::read_compression_codec(&conn).await?;
+        Ok(Self { conn, codec })
+    }
+
+    async fn read_compression_codec(conn: &Connection) -> Result<CompressionCodec> {
+        let mut rows = conn
+            .query(
+                "SELECT value FROM fs_config WHERE key = 'compression_codec'",
+                (),
+            )
+            .await?;
+        let Some(row) = rows.next().await? else {
+            return Ok(DEFAULT_COMPRESSION_CODEC);
+        };
+        let value = row
+            .get_value(0)
+            .ok()
+            .and_then(|v| match v {
+                Value::Text(s) => Some(s),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Ok(CompressionCodec::parse(&value))
+    }
+
+    /// Expose the raw connection for schema-level queries `Filesystem`
+    /// still needs directly (reading the configured chunk size).
+    pub(crate) fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    async fn initialize_schema(conn: &Connection) -> Result<()> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_config (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+            (),
+        )
+        .await?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_inode (
+                ino INTEGER PRIMARY KEY AUTOINCREMENT,
+                mode INTEGER NOT NULL,
+                uid INTEGER NOT NULL DEFAULT 0,
+                gid INTEGER NOT NULL DEFAULT 0,
+                size INTEGER NOT NULL DEFAULT 0,
+                atime INTEGER NOT NULL,
+                mtime INTEGER NOT NULL,
+                ctime INTEGER NOT NULL,
+                rdev INTEGER NOT NULL DEFAULT 0
+            )",
+            (),
+        )
+        .await?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_dentry (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                parent_ino INTEGER NOT NULL,
+                ino INTEGER NOT NULL,
+                UNIQUE(parent_ino, name)
+            )",
+            (),
+        )
+        .await?;
+
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_fs_dentry_parent
+            ON fs_dentry(parent_ino, name)",
+            (),
+        )
+        .await?;
+
+        // Content-addressed block store: each distinct chunk of bytes is
+        // stored once, keyed by its BLAKE3 hash, with a refcount tracking
+        // how many (ino, chunk_index) entries currently point at it.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_block (
+                hash BLOB PRIMARY KEY,
+                data BLOB NOT NULL,
+                length INTEGER NOT NULL,
+                codec INTEGER NOT NULL DEFAULT 0,
+                refcount INTEGER NOT NULL DEFAULT 0
+            )",
+            (),
+        )
+        .await?;
+
+        // Data chunks table, mapping each file's chunk index to the hash of
+        // the block holding its bytes.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_data (
+                ino INTEGER NOT NULL,
+                chunk_index INTEGER NOT NULL,
+                hash BLOB NOT NULL,
+                PRIMARY KEY (ino, chunk_index)
+            )",
+            (),
+        )
+        .await?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_symlink (
+                ino INTEGER PRIMARY KEY,
+                target TEXT NOT NULL
+            )",
+            (),
+        )
+        .await?;
+
+        // Per-file extended attributes, kept in a side table decoupled from
+        // content (the overlayfs pattern), so xattrs never touch fs_data.
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS fs_xattr (
+                ino INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                value BLOB NOT NULL,
+                PRIMARY KEY (ino, name)
+            )",
+            (),
+        )
+        .await?;
+
+        let mut rows = conn
+            .query("SELECT value FROM fs_config WHERE key = 'chunk_size'", ())
+            .await?;
+        if rows.next().await?.is_none() {
+            conn.execute(
+                "INSERT INTO fs_config (key, value) VALUES ('chunk_size', ?)",
+                (DEFAULT_CHUNK_SIZE.to_string(),),
+            )
+            .await?;
+        }
+
+        let mut rows = conn
+            .query(
+                "SELECT value FROM fs_config WHERE key = 'compression_codec'",
+                (),
+            )
+            .await?;
+        if rows.next().await?.is_none() {
+            conn.execute(
+                "INSERT INTO fs_config (key, value) VALUES ('compression_codec', ?)",
+                (DEFAULT_COMPRESSION_CODEC.as_str(),),
+            )
+            .await?;
+        }
+
+        let mut rows = conn
+            .query("SELECT ino FROM fs_inode WHERE ino = ?", (ROOT_INO,))
+            .await?;
+        if rows.next().await?.is_none() {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+            conn.execute(
+                "INSERT INTO fs_inode (ino, mode, uid, gid, size, atime, mtime, ctime)
+                VALUES (?, ?, 0, 0, 0, ?, ?, ?)",
+                (ROOT_INO, DEFAULT_DIR_MODE as i64, now, now, now),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl VfsBackend for SqliteBackend {
+    async fn lookup(&self, parent_ino: i64, name: &str) -> Result<Option<i64>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT ino FROM fs_dentry WHERE parent_ino = ? AND name = ?",
+                (parent_ino, name),
+            )
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            Ok(row.get_value(0).ok().and_then(|v| v.as_integer().copied()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn get_inode(&self, ino: i64) -> Result<Option<InodeRecord>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT mode, uid, gid, size, atime, mtime, ctime, rdev FROM fs_inode WHERE ino = ?",
+                (ino,),
+            )
+            .await?;
+
+        let Some(row) = rows.next().await? else {
+            return Ok(None);
+        };
+
+        let get_int = |i: usize| {
+            row.get_value(i)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0)
+        };
+
+        Ok(Some(InodeRecord {
+            mode: get_int(0) as u32,
+            uid: get_int(1) as u32,
+            gid: get_int(2) as u32,
+            size: get_int(3),
+            atime: get_int(4),
+            mtime: get_int(5),
+            ctime: get_int(6),
+            rdev: get_int(7) as u32,
+        }))
+    }
+
+    async fn create_inode(
+        &self,
+        mode: u32,
+        uid: u32,
+        gid: u32,
+        size: i64,
+        rdev: u32,
+    ) -> Result<i64> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.conn
+            .execute(
+                "INSERT INTO fs_inode (mode, uid, gid, size, atime, mtime, ctime, rdev)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+                (mode as i64, uid, gid, size, now, now, now, rdev),
+            )
+            .await?;
+
+        let mut rows = self.conn.query("SELECT last_insert_rowid()", ()).await?;
+        if let Some(row) = rows.next().await? {
+            row.get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .ok_or_else(|| anyhow::anyhow!("Failed to get inode"))
+        } else {
+            anyhow::bail!("Failed to get inode");
+        }
+    }
+
+    async fn update_inode_size(&self, ino: i64, size: i64, mtime: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_inode SET size = ?, mtime = ? WHERE ino = ?",
+                (size, mtime, ino),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_mode(&self, ino: i64, mode: u32, ctime: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_inode SET mode = ?, ctime = ? WHERE ino = ?",
+                (mode, ctime, ino),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_owner(&self, ino: i64, uid: u32, gid: u32, ctime: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_inode SET uid = ?, gid = ?, ctime = ? WHERE ino = ?",
+                (uid, gid, ctime, ino),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_times(&self, ino: i64, atime: i64, mtime: i64, ctime: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_inode SET atime = ?, mtime = ?, ctime = ? WHERE ino = ?",
+                (atime, mtime, ctime, ino),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_inode(&self, ino: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM fs_inode WHERE ino = ?", (ino,))
+            .await?;
+        Ok(())
+    }
+
+    async fn insert_dentry(&self, parent_ino: i64, name: &str, ino: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO fs_dentry (name, parent_ino, ino) VALUES (?, ?, ?)",
+                (name, parent_ino, ino),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn remove_dentry(&self, parent_ino: i64, name: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "DELETE FROM fs_dentry WHERE parent_ino = ? AND name = ?",
+                (parent_ino, name),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn list_dentries(&self, ino: i64) -> Result<Vec<String>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT name FROM fs_dentry WHERE parent_ino = ? ORDER BY name",
+                (ino,),
+            )
+            .await?;
+
+        let mut names = Vec::new();
+        while let Some(row) = rows.next().await? {
+            if let Ok(Value::Text(name)) = row.get_value(0) {
+                if !name.is_empty() {
+                    names.push(name);
+                }
+            }
+        }
+        Ok(names)
+    }
+
+    async fn dentry_count(&self, ino: i64) -> Result<i64> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COUNT(*) FROM fs_dentry WHERE parent_ino = ?",
+                (ino,),
+            )
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            Ok(row
+                .get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0))
+        } else {
+            Ok(0)
+        }
+    }
+
+    async fn link_count(&self, ino: i64) -> Result<u32> {
+        let mut rows = self
+            .conn
+            .query("SELECT COUNT(*) FROM fs_dentry WHERE ino = ?", (ino,))
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            Ok(row
+                .get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0) as u32)
+        } else {
+            Ok(0)
+        }
+    }
+
+    async fn chunk_layout(&self, ino: i64) -> Result<Vec<(i64, Vec<u8>, usize)>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT d.chunk_index, d.hash, b.length
+                FROM fs_data d JOIN fs_block b ON b.hash = d.hash
+                WHERE d.ino = ? ORDER BY d.chunk_index",
+                (ino,),
+            )
+            .await?;
+
+        let mut layout = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let chunk_index = row
+                .get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0);
+            let hash = match row.get_value(1) {
+                Ok(Value::Blob(hash)) => hash,
+                _ => continue,
+            };
+            let len = row
+                .get_value(2)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0) as usize;
+            layout.push((chunk_index, hash, len));
+        }
+        Ok(layout)
+    }
+
+    async fn chunk_hashes(&self, ino: i64) -> Result<Vec<Vec<u8>>> {
+        let mut rows = self
+            .conn
+            .query("SELECT hash FROM fs_data WHERE ino = ?", (ino,))
+            .await?;
+
+        let mut hashes = Vec::new();
+        while let Some(row) = rows.next().await? {
+            if let Ok(Value::Blob(hash)) = row.get_value(0) {
+                hashes.push(hash);
+            }
+        }
+        Ok(hashes)
+    }
+
+    async fn block_data(&self, hash: &[u8]) -> Result<Option<Vec<u8>>> {
+        let mut rows = self
+            .conn
+            .query("SELECT data, codec FROM fs_block WHERE hash = ?", (hash,))
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            if let Ok(Value::Blob(data)) = row.get_value(0) {
+                let codec_tag = row
+                    .get_value(1)
+                    .ok()
+                    .and_then(|v| v.as_integer().copied())
+                    .unwrap_or(0);
+                return Ok(Some(decompress_block(data, codec_tag)?));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn read_file_data(&self, ino: i64) -> Result<Vec<u8>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT b.data, b.codec FROM fs_data d JOIN fs_block b ON b.hash = d.hash
+                WHERE d.ino = ? ORDER BY d.chunk_index",
+                (ino,),
+            )
+            .await?;
+
+        let mut data = Vec::new();
+        while let Some(row) = rows.next().await? {
+            if let Ok(Value::Blob(chunk)) = row.get_value(0) {
+                let codec_tag = row
+                    .get_value(1)
+                    .ok()
+                    .and_then(|v| v.as_integer().copied())
+                    .unwrap_or(0);
+                data.extend_from_slice(&decompress_block(chunk, codec_tag)?);
+            }
+        }
+        Ok(data)
+    }
+
+    async fn put_block(&self, data: &[u8]) -> Result<[u8; 32]> {
+        let hash = *blake3::hash(data).as_bytes();
+        let (stored, codec_tag) = compress_block(data, self.codec)?;
+        self.conn
+            .execute(
+                "INSERT INTO fs_block (hash, data, length, codec, refcount) VALUES (?, ?, ?, ?, 1)
+                ON CONFLICT(hash) DO UPDATE SET refcount = refcount + 1",
+                (hash.to_vec(), stored, data.len() as i64, codec_tag),
+            )
+            .await?;
+        Ok(hash)
+    }
+
+    async fn release_block(&self, hash: &[u8]) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_block SET refcount = refcount - 1 WHERE hash = ?",
+                (hash,),
+            )
+            .await?;
+        self.conn
+            .execute(
+                "DELETE FROM fs_block WHERE hash = ? AND refcount <= 0",
+                (hash,),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn insert_chunk(&self, ino: i64, chunk_index: i64, hash: &[u8]) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO fs_data (ino, chunk_index, hash) VALUES (?, ?, ?)",
+                (ino, chunk_index, hash),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn update_chunk_hash(&self, ino: i64, chunk_index: i64, hash: &[u8]) -> Result<()> {
+        self.conn
+            .execute(
+                "UPDATE fs_data SET hash = ? WHERE ino = ? AND chunk_index = ?",
+                (hash, ino, chunk_index),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_chunk(&self, ino: i64, chunk_index: i64) -> Result<()> {
+        self.conn
+            .execute(
+                "DELETE FROM fs_data WHERE ino = ? AND chunk_index = ?",
+                (ino, chunk_index),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_all_chunks(&self, ino: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM fs_data WHERE ino = ?", (ino,))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_symlink(&self, ino: i64) -> Result<Option<String>> {
+        let mut rows = self
+            .conn
+            .query("SELECT target FROM fs_symlink WHERE ino = ?", (ino,))
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            let target = row
+                .get_value(0)
+                .ok()
+                .and_then(|v| match v {
+                    Value::Text(s) => Some(s.to_string()),
+                    _ => None,
+                })
+                .ok_or_else(|| anyhow::anyhow!("Invalid symlink target"))?;
+            Ok(Some(target))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn set_symlink(&self, ino: i64, target: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO fs_symlink (ino, target) VALUES (?, ?)",
+                (ino, target),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_symlink(&self, ino: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM fs_symlink WHERE ino = ?", (ino,))
+            .await?;
+        Ok(())
+    }
+
+    async fn dedup_stats(&self) -> Result<DedupStats> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COUNT(b.hash), COALESCE(SUM(LENGTH(b.data)), 0)
+                FROM fs_block b",
+                (),
+            )
+            .await?;
+        let (block_count, physical_bytes) = if let Some(row) = rows.next().await? {
+            let block_count = row
+                .get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0);
+            let physical_bytes = row
+                .get_value(1)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0);
+            (block_count, physical_bytes)
+        } else {
+            (0, 0)
+        };
+
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COALESCE(SUM(b.length), 0)
+                FROM fs_data d JOIN fs_block b ON b.hash = d.hash",
+                (),
+            )
+            .await?;
+        let logical_bytes = if let Some(row) = rows.next().await? {
+            row.get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0)
+        } else {
+            0
+        };
+
+        Ok(DedupStats {
+            logical_bytes,
+            physical_bytes,
+            block_count,
+        })
+    }
+
+    async fn set_xattr(&self, ino: i64, name: &str, value: &[u8]) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT INTO fs_xattr (ino, name, value) VALUES (?, ?, ?)
+                ON CONFLICT(ino, name) DO UPDATE SET value = excluded.value",
+                (ino, name, value),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_xattr(&self, ino: i64, name: &str) -> Result<Option<Vec<u8>>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT value FROM fs_xattr WHERE ino = ? AND name = ?",
+                (ino, name),
+            )
+            .await?;
+
+        if let Some(row) = rows.next().await? {
+            if let Ok(Value::Blob(value)) = row.get_value(0) {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn list_xattrs(&self, ino: i64) -> Result<Vec<String>> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT name FROM fs_xattr WHERE ino = ? ORDER BY name",
+                (ino,),
+            )
+            .await?;
+
+        let mut names = Vec::new();
+        while let Some(row) = rows.next().await? {
+            if let Ok(Value::Text(name)) = row.get_value(0) {
+                names.push(name);
+            }
+        }
+        Ok(names)
+    }
+
+    async fn remove_xattr(&self, ino: i64, name: &str) -> Result<()> {
+        self.conn
+            .execute(
+                "DELETE FROM fs_xattr WHERE ino = ? AND name = ?",
+                (ino, name),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_xattrs(&self, ino: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM fs_xattr WHERE ino = ?", (ino,))
+            .await?;
+        Ok(())
+    }
+
+    async fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        let mut report = FsckReport::default();
+
+        // Orphan chunks: fs_data rows whose ino no longer has an inode.
+        // Their blocks need a refcount release, not just row deletion, so
+        // collect the hashes before (optionally) deleting the rows.
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT hash FROM fs_data WHERE ino NOT IN (SELECT ino FROM fs_inode)",
+                (),
+            )
+            .await?;
+        let mut orphan_hashes = Vec::new();
+        while let Some(row) = rows.next().await? {
+            if let Ok(Value::Blob(hash)) = row.get_value(0) {
+                orphan_hashes.push(hash);
+            }
+        }
+        report.orphan_chunks = orphan_hashes.len() as i64;
+        if repair && !orphan_hashes.is_empty() {
+            self.conn
+                .execute(
+                    "DELETE FROM fs_data WHERE ino NOT IN (SELECT ino FROM fs_inode)",
+                    (),
+                )
+                .await?;
+            for hash in &orphan_hashes {
+                self.release_block(hash).await?;
+            }
+        }
+
+        // Orphan symlinks: fs_symlink rows whose ino no longer has an inode.
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COUNT(*) FROM fs_symlink WHERE ino NOT IN (SELECT ino FROM fs_inode)",
+                (),
+            )
+            .await?;
+        report.orphan_symlinks = rows
+            .next()
+            .await?
+            .and_then(|r| r.get_value(0).ok().and_then(|v| v.as_integer().copied()))
+            .unwrap_or(0);
+        if repair && report.orphan_symlinks > 0 {
+            self.conn
+                .execute(
+                    "DELETE FROM fs_symlink WHERE ino NOT IN (SELECT ino FROM fs_inode)",
+                    (),
+                )
+                .await?;
+        }
+
+        // Dangling dentries: fs_dentry rows whose ino or parent_ino no
+        // longer has an inode.
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COUNT(*) FROM fs_dentry
+                WHERE ino NOT IN (SELECT ino FROM fs_inode)
+                OR parent_ino NOT IN (SELECT ino FROM fs_inode)",
+                (),
+            )
+            .await?;
+        report.dangling_dentries = rows
+            .next()
+            .await?
+            .and_then(|r| r.get_value(0).ok().and_then(|v| v.as_integer().copied()))
+            .unwrap_or(0);
+        if repair && report.dangling_dentries > 0 {
+            self.conn
+                .execute(
+                    "DELETE FROM fs_dentry
+                    WHERE ino NOT IN (SELECT ino FROM fs_inode)
+                    OR parent_ino NOT IN (SELECT ino FROM fs_inode)",
+                    (),
+                )
+                .await?;
+        }
+
+        // Non-contiguous chunk indices: a file's chunk_index values must
+        // be exactly 0..count. Detected, but not auto-repaired here, since
+        // fixing it safely means rewriting file content, not just deleting
+        // rows.
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT ino FROM fs_data GROUP BY ino HAVING MAX(chunk_index) + 1 != COUNT(*)",
+                (),
+            )
+            .await?;
+        let mut non_contiguous = 0i64;
+        while rows.next().await?.is_some() {
+            non_contiguous += 1;
+        }
+        report.non_contiguous_chunk_files = non_contiguous;
+
+        // Size mismatches: a regular file's stored size should equal the
+        // summed byte length of its chunks.
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT i.ino, COALESCE(SUM(b.length), 0) AS actual
+                FROM fs_inode i
+                LEFT JOIN fs_data d ON d.ino = i.ino
+                LEFT JOIN fs_block b ON b.hash = d.hash
+                WHERE (i.mode & ?) = ?
+                GROUP BY i.ino, i.size
+                HAVING i.size != actual",
+                (S_IFMT as i64, S_IFREG as i64),
+            )
+            .await?;
+        let mut mismatches = Vec::new();
+        while let Some(row) = rows.next().await? {
+            let ino = row
+                .get_value(0)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0);
+            let actual = row
+                .get_value(1)
+                .ok()
+                .and_then(|v| v.as_integer().copied())
+                .unwrap_or(0);
+            mismatches.push((ino, actual));
+        }
+        report.size_mismatches = mismatches.len() as i64;
+        if repair {
+            for (ino, actual) in mismatches {
+                self.conn
+                    .execute("UPDATE fs_inode SET size = ? WHERE ino = ?", (actual, ino))
+                    .await?;
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn gc(&self) -> Result<i64> {
+        let mut rows = self
+            .conn
+            .query(
+                "SELECT COALESCE(SUM(LENGTH(data)), 0) FROM fs_block
+                WHERE hash NOT IN (SELECT DISTINCT hash FROM fs_data)",
+                (),
+            )
+            .await?;
+        let reclaimed = rows
+            .next()
+            .await?
+            .and_then(|r| r.get_value(0).ok().and_then(|v| v.as_integer().copied()))
+            .unwrap_or(0);
+
+        self.conn
+            .execute(
+                "DELETE FROM fs_block WHERE hash NOT IN (SELECT DISTINCT hash FROM fs_data)",
+                (),
+            )
+            .await?;
+
+        self.conn.execute("VACUUM", ()).await?;
+
+        Ok(reclaimed)
+    }
+
+    async fn begin(&self) -> Result<()> {
+        self.conn.execute("BEGIN", ()).await?;
+        Ok(())
+    }
+
+    async fn commit(&self) -> Result<()> {
+        self.conn.execute("COMMIT", ()).await?;
+        Ok(())
+    }
+
+    async fn rollback(&self) -> Result<()> {
+        self.conn.execute("ROLLBACK", ()).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use turso::{Builder, Value};
+
+    async fn test_backend() -> Result<SqliteBackend> {
+        let db = Builder::new_local(":memory:").build().await?;
+        let conn = Arc::new(db.connect()?);
+        SqliteBackend::new(conn).await
+    }
+
+    #[tokio::test]
+    async fn test_schema_seeds_default_chunk_size() -> Result<()> {
+        let backend = test_backend().await?;
+
+        let mut rows = backend
+            .connection()
+            .query("SELECT value FROM fs_config WHERE key = 'chunk_size'", ())
+            .await?;
+
+        let row = rows.next().await?.expect("chunk_size config should exist");
+        let value = row
+            .get_value(0)
+            .ok()
+            .and_then(|v| match v {
+                Value::Text(s) => Some(s),
+                _ => None,
+            })
+            .expect("chunk_size should be a text value");
+
+        assert_eq!(value, DEFAULT_CHUNK_SIZE.to_string());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schema_seeds_root_inode() -> Result<()> {
+        let backend = test_backend().await?;
+
+        let record = backend
+            .get_inode(ROOT_INO)
+            .await?
+            .expect("root inode should exist");
+        assert_eq!(record.mode, DEFAULT_DIR_MODE);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_clean_store_reports_nothing() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 0, 0).await?;
+        backend.insert_dentry(ROOT_INO, "f.txt", ino).await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report, FsckReport::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_detects_and_repairs_orphan_chunk() -> Result<()> {
+        let backend = test_backend().await?;
+        let hash = backend.put_block(b"orphaned").await?;
+        // ino 999 has no fs_inode row at all.
+        backend.insert_chunk(999, 0, &hash).await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report.orphan_chunks, 1);
+
+        let repaired = backend.fsck(true).await?;
+        assert_eq!(repaired.orphan_chunks, 1);
+        assert_eq!(backend.chunk_layout(999).await?.len(), 0);
+        // The orphan's block must have been released along with the row.
+        assert_eq!(backend.dedup_stats().await?.block_count, 0);
+
+        assert_eq!(backend.fsck(false).await?, FsckReport::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_detects_and_repairs_orphan_symlink() -> Result<()> {
+        let backend = test_backend().await?;
+        backend.set_symlink(999, "/target").await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report.orphan_symlinks, 1);
+
+        backend.fsck(true).await?;
+        assert_eq!(backend.get_symlink(999).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_detects_and_repairs_dangling_dentry() -> Result<()> {
+        let backend = test_backend().await?;
+        // Points at an inode that doesn't exist.
+        backend.insert_dentry(ROOT_INO, "ghost", 999).await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report.dangling_dentries, 1);
+
+        backend.fsck(true).await?;
+        assert_eq!(backend.lookup(ROOT_INO, "ghost").await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_detects_and_repairs_size_mismatch() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 12345, 0).await?;
+        let hash = backend.put_block(b"actual-bytes").await?;
+        backend.insert_chunk(ino, 0, &hash).await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report.size_mismatches, 1);
+
+        backend.fsck(true).await?;
+        let record = backend.get_inode(ino).await?.unwrap();
+        assert_eq!(record.size, "actual-bytes".len() as i64);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_detects_non_contiguous_chunk_indices() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 0, 0).await?;
+        let hash = backend.put_block(b"chunk").await?;
+        // chunk_index 0 and 2, skipping 1.
+        backend.insert_chunk(ino, 0, &hash).await?;
+        backend.insert_chunk(ino, 2, &hash).await?;
+
+        let report = backend.fsck(false).await?;
+        assert_eq!(report.non_contiguous_chunk_files, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gc_leaves_referenced_blocks_alone() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 5, 0).await?;
+        let hash = backend.put_block(b"hello").await?;
+        backend.insert_chunk(ino, 0, &hash).await?;
+
+        let reclaimed = backend.gc().await?;
+        assert_eq!(reclaimed, 0);
+        assert_eq!(backend.block_data(&hash).await?, Some(b"hello".to_vec()));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gc_deletes_block_with_no_chunk_reference() -> Result<()> {
+        let backend = test_backend().await?;
+        // A block inserted with no corresponding fs_data row at all — the
+        // kind of leftover an interrupted write could produce.
+        let hash = backend.put_block(b"unreferenced").await?;
+
+        let reclaimed = backend.gc().await?;
+        assert_eq!(reclaimed, "unreferenced".len() as i64);
+        assert_eq!(backend.block_data(&hash).await?, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rollback_undoes_writes_since_begin() -> Result<()> {
+        let backend = test_backend().await?;
+
+        backend.begin().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 0, 0).await?;
+        backend.insert_dentry(ROOT_INO, "f.txt", ino).await?;
+        backend.rollback().await?;
+
+        assert_eq!(backend.lookup(ROOT_INO, "f.txt").await?, None);
+        assert!(backend.get_inode(ino).await?.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commit_keeps_writes_since_begin() -> Result<()> {
+        let backend = test_backend().await?;
+
+        backend.begin().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 0, 0).await?;
+        backend.insert_dentry(ROOT_INO, "f.txt", ino).await?;
+        backend.commit().await?;
+
+        assert_eq!(backend.lookup(ROOT_INO, "f.txt").await?, Some(ino));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_schema_seeds_default_compression_codec() -> Result<()> {
+        let backend = test_backend().await?;
+
+        let mut rows = backend
+            .connection()
+            .query(
+                "SELECT value FROM fs_config WHERE key = 'compression_codec'",
+                (),
+            )
+            .await?;
+
+        let row = rows
+            .next()
+            .await?
+            .expect("compression_codec config should exist");
+        let value = row
+            .get_value(0)
+            .ok()
+            .and_then(|v| match v {
+                Value::Text(s) => Some(s),
+                _ => None,
+            })
+            .expect("compression_codec should be a text value");
+
+        assert_eq!(value, DEFAULT_COMPRESSION_CODEC.as_str());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_put_block_compresses_highly_compressible_data() -> Result<()> {
+        let backend = test_backend().await?;
+        let data = vec![b'x'; 4096];
+        let hash = backend.put_block(&data).await?;
+
+        let mut rows = backend
+            .connection()
+            .query(
+                "SELECT LENGTH(data), length, codec FROM fs_block WHERE hash = ?",
+                (hash.to_vec(),),
+            )
+            .await?;
+        let row = rows.next().await?.expect("block row should exist");
+        let stored_len = row.get_value(0).ok().and_then(|v| v.as_integer().copied()).unwrap_or(0);
+        let logical_len = row.get_value(1).ok().and_then(|v| v.as_integer().copied()).unwrap_or(0);
+        let codec = row.get_value(2).ok().and_then(|v| v.as_integer().copied()).unwrap_or(0);
+
+        assert_eq!(logical_len, data.len() as i64);
+        assert_eq!(codec, 1);
+        assert!(stored_len < data.len() as i64);
+
+        // Reading it back must still return the original, uncompressed bytes.
+        assert_eq!(backend.block_data(&hash).await?, Some(data));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_put_block_falls_back_to_raw_when_compression_does_not_help() -> Result<()> {
+        let backend = test_backend().await?;
+        // A single byte compresses worse than it's stored raw under any
+        // codec's framing overhead.
+        let data = vec![7u8];
+        let hash = backend.put_block(&data).await?;
+
+        let mut rows = backend
+            .connection()
+            .query(
+                "SELECT data, codec FROM fs_block WHERE hash = ?",
+                (hash.to_vec(),),
+            )
+            .await?;
+        let row = rows.next().await?.expect("block row should exist");
+        let codec = row.get_value(1).ok().and_then(|v| v.as_integer().copied()).unwrap_or(-1);
+        assert_eq!(codec, 0);
+
+        assert_eq!(backend.block_data(&hash).await?, Some(data));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chunk_layout_length_is_logical_not_compressed() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 4096, 0).await?;
+        let data = vec![b'y'; 4096];
+        let hash = backend.put_block(&data).await?;
+        backend.insert_chunk(ino, 0, &hash).await?;
+
+        let layout = backend.chunk_layout(ino).await?;
+        assert_eq!(layout.len(), 1);
+        assert_eq!(layout[0].2, 4096);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stats_logical_bytes_ignore_compression() -> Result<()> {
+        let backend = test_backend().await?;
+        let ino = backend.create_inode(S_IFREG | 0o644, 0, 0, 4096, 0).await?;
+        let data = vec![b'z'; 4096];
+        let hash = backend.put_block(&data).await?;
+        backend.insert_chunk(ino, 0, &hash).await?;
+
+        let stats = backend.dedup_stats().await?;
+        assert_eq!(stats.logical_bytes, 4096);
+        // Highly compressible data, so physical storage should be smaller.
+        assert!(stats.physical_bytes < stats.logical_bytes);
+        Ok(())
+    }
+}
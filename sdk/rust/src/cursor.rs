@@ -0,0 +1,154 @@
+//! Buffered write handle for [`Filesystem`](crate::Filesystem).
+//!
+//! `Filesystem::write_at` already touches only the chunks overlapping a
+//! given byte range, but a naive caller doing many small sequential writes
+//! (as an agent streaming output, or a FUSE `write` callback, would) still
+//! pays one backend round trip per call. [`FileCursor`] accumulates those
+//! writes in memory and flushes a whole buffer's worth at once, in the
+//! spirit of a standard write-back cache buffer.
+
+use anyhow::Result;
+
+use crate::Filesystem;
+
+/// A sequential, buffered write cursor over one file.
+///
+/// Writes accumulate in an in-memory buffer and are flushed to the backend
+/// once the buffer reaches `chunk_size` bytes, so `n` small sequential
+/// writes cost roughly `n / (chunk_size / write_len)` backend round trips
+/// instead of `n`. [`Self::pos`] always reports the logical position
+/// including unflushed bytes, so callers don't need to know about the
+/// buffering to reason about where the next write will land.
+pub struct FileCursor {
+    fs: Filesystem,
+    path: String,
+    flushed_pos: usize,
+    buffer: Vec<u8>,
+}
+
+impl FileCursor {
+    /// Open `path` for buffered writing, positioned at its current end of
+    /// file (or offset 0 if it doesn't exist yet).
+    pub async fn open(fs: Filesystem, path: &str) -> Result<Self> {
+        let flushed_pos = fs.stat(path).await?.map(|s| s.size as usize).unwrap_or(0);
+        Ok(Self {
+            fs,
+            path: path.to_string(),
+            flushed_pos,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// The logical position the next write will land at, including any
+    /// bytes still sitting in the unflushed buffer.
+    pub fn pos(&self) -> usize {
+        self.flushed_pos + self.buffer.len()
+    }
+
+    /// Buffer `data` for writing. Flushes automatically once the buffer
+    /// reaches the filesystem's configured chunk size.
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= self.fs.chunk_size() {
+            self.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// Write out any buffered bytes, in a single `write_at` call.
+    pub async fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.fs.write_at(&self.path, self.flushed_pos, &self.buffer).await?;
+        self.flushed_pos += self.buffer.len();
+        self.buffer.clear();
+        Ok(())
+    }
+
+    /// Flush any remaining buffered bytes and consume the cursor.
+    pub async fn close(mut self) -> Result<()> {
+        self.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    async fn create_test_fs() -> Result<(Filesystem, tempfile::TempDir)> {
+        let dir = tempdir()?;
+        let db_path = dir.path().join("test.db");
+        let fs = Filesystem::new(db_path.to_str().unwrap()).await?;
+        Ok((fs, dir))
+    }
+
+    #[tokio::test]
+    async fn test_cursor_buffers_small_writes_until_chunk_size() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"").await?;
+
+        let mut cursor = FileCursor::open(fs.clone(), "/f.txt").await?;
+        cursor.write(b"hello").await?;
+
+        // Well under chunk_size, so nothing should have been flushed yet.
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"");
+        assert_eq!(cursor.pos(), 5);
+
+        cursor.close().await?;
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cursor_flushes_once_buffer_crosses_chunk_size() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"").await?;
+
+        let chunk_size = fs.chunk_size();
+        let mut cursor = FileCursor::open(fs.clone(), "/f.txt").await?;
+        cursor.write(&vec![b'x'; chunk_size]).await?;
+
+        // The buffer reached chunk_size, so `write` must have flushed
+        // already, without needing an explicit `close`.
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap().len(), chunk_size);
+        assert_eq!(cursor.pos(), chunk_size);
+
+        cursor.close().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cursor_appends_across_multiple_writes() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"").await?;
+
+        let mut cursor = FileCursor::open(fs.clone(), "/f.txt").await?;
+        cursor.write(b"foo").await?;
+        cursor.write(b"bar").await?;
+        cursor.write(b"baz").await?;
+        cursor.close().await?;
+
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"foobarbaz");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cursor_opens_at_existing_end_of_file() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"existing").await?;
+
+        let mut cursor = FileCursor::open(fs.clone(), "/f.txt").await?;
+        assert_eq!(cursor.pos(), "existing".len());
+
+        cursor.write(b"-appended").await?;
+        cursor.close().await?;
+
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"existing-appended");
+
+        Ok(())
+    }
+}
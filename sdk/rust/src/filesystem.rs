@@ -1,14 +1,25 @@
 use anyhow::Result;
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH};
 use turso::{Builder, Connection, Value};
 
+use crate::backend::{SqliteBackend, VfsBackend};
+
+/// Default capacity of the per-`Filesystem` path resolution cache.
+const DEFAULT_PATH_CACHE_CAPACITY: usize = 4096;
+
 // File types for mode field
 const S_IFMT: u32 = 0o170000; // File type mask
+const S_IFSOCK: u32 = 0o140000; // Socket
+const S_IFLNK: u32 = 0o120000; // Symbolic link
 const S_IFREG: u32 = 0o100000; // Regular file
+const S_IFBLK: u32 = 0o060000; // Block device
 const S_IFDIR: u32 = 0o040000; // Directory
-const S_IFLNK: u32 = 0o120000; // Symbolic link
+const S_IFCHR: u32 = 0o020000; // Character device
+const S_IFIFO: u32 = 0o010000; // FIFO
 
 // Default permissions
 const DEFAULT_FILE_MODE: u32 = S_IFREG | 0o644; // Regular file, rw-r--r--
@@ -17,6 +28,201 @@ const DEFAULT_DIR_MODE: u32 = S_IFDIR | 0o755; // Directory, rwxr-xr-x
 const ROOT_INO: i64 = 1;
 const DEFAULT_CHUNK_SIZE: usize = 4096;
 
+// FastCDC content-defined chunking parameters. Cut points are only
+// considered once `CDC_MIN_CHUNK_SIZE` bytes have accumulated, using the
+// smaller mask (fewer bits, so more cut points and smaller expected chunks)
+// below `CDC_NORMAL_SIZE` and the larger one above it, with chunks forced to end
+// at `CDC_MAX_CHUNK_SIZE` regardless of whether a cut point was found.
+const CDC_MIN_CHUNK_SIZE: usize = 2 * 1024;
+const CDC_NORMAL_SIZE: usize = 16 * 1024;
+const CDC_MAX_CHUNK_SIZE: usize = 64 * 1024;
+const CDC_MASK_SMALL: u64 = (1 << 14) - 1;
+const CDC_MASK_LARGE: u64 = (1 << 17) - 1;
+
+/// Gear table of pseudorandom 64-bit constants, one per input byte value,
+/// used to feed the rolling hash `h = (h << 1) + GEAR[byte]` that drives
+/// chunk boundary detection.
+const GEAR: [u64; 256] = build_gear_table();
+
+const fn build_gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E3779B97F4A7C15;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        table[i] = z;
+        i += 1;
+    }
+    table
+}
+
+/// Split `data` into content-defined chunks, FastCDC-style: a declared cut
+/// point ends a chunk once the minimum size is reached, and every chunk is
+/// forced to end by the maximum size even if no cut point was found.
+fn cdc_chunks(data: &[u8]) -> Vec<&[u8]> {
+    if data.is_empty() {
+        return Vec::new();
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+    while start < data.len() {
+        let remaining = &data[start..];
+        let limit = remaining.len().min(CDC_MAX_CHUNK_SIZE);
+
+        if remaining.len() <= CDC_MIN_CHUNK_SIZE {
+            chunks.push(remaining);
+            break;
+        }
+
+        let mut hash: u64 = 0;
+        let mut cut = limit;
+        for i in 0..limit {
+            hash = (hash << 1).wrapping_add(GEAR[remaining[i] as usize]);
+            if i + 1 < CDC_MIN_CHUNK_SIZE {
+                continue;
+            }
+            let mask = if i + 1 < CDC_NORMAL_SIZE {
+                CDC_MASK_SMALL
+            } else {
+                CDC_MASK_LARGE
+            };
+            if hash & mask == 0 {
+                cut = i + 1;
+                break;
+            }
+        }
+
+        chunks.push(&remaining[..cut]);
+        start += cut;
+    }
+
+    chunks
+}
+
+/// Read a little-endian `u16` from an [`ArchiveEntry`] index stream.
+fn read_u16(reader: &mut impl Read) -> Result<u16> {
+    let mut buf = [0u8; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+/// Read a little-endian `u32` from an archive stream.
+fn read_u32(reader: &mut impl Read) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Read a little-endian `u64` from an archive stream.
+fn read_u64(reader: &mut impl Read) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Read a little-endian `i64` from an archive stream.
+fn read_i64(reader: &mut impl Read) -> Result<i64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(i64::from_le_bytes(buf))
+}
+
+/// Logical vs. physical byte counts for the content-addressed block store,
+/// showing how much space block-level deduplication and per-chunk
+/// compression have together saved. `logical_bytes` counts each chunk at
+/// its original, uncompressed length; `physical_bytes` counts what's
+/// actually stored on disk, after both dedup and compression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DedupStats {
+    pub logical_bytes: i64,
+    pub physical_bytes: i64,
+    pub block_count: i64,
+}
+
+impl DedupStats {
+    /// How many logical bytes each physical byte represents, e.g. `2.0`
+    /// means the store takes half the space its logical content would
+    /// without dedup/compression. `1.0` if there's nothing stored yet.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.physical_bytes == 0 {
+            1.0
+        } else {
+            self.logical_bytes as f64 / self.physical_bytes as f64
+        }
+    }
+}
+
+/// Counts of the inconsistencies [`Filesystem::fsck`] found (and, if asked,
+/// repaired) in a store that manually cascades deletes instead of relying
+/// on foreign keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FsckReport {
+    /// `fs_data` rows whose `ino` has no matching `fs_inode` row.
+    pub orphan_chunks: i64,
+    /// `fs_symlink` rows whose `ino` has no matching `fs_inode` row.
+    pub orphan_symlinks: i64,
+    /// `fs_dentry` rows whose `ino` or `parent_ino` has no matching
+    /// `fs_inode` row.
+    pub dangling_dentries: i64,
+    /// Files whose chunk indices aren't a contiguous `0..n` run. Reported
+    /// but never auto-repaired, since fixing the chunk topology safely
+    /// would require rewriting the file's content, not just deleting rows.
+    pub non_contiguous_chunk_files: i64,
+    /// Regular files whose stored `size` disagrees with the actual byte
+    /// length of their chunks.
+    pub size_mismatches: i64,
+}
+
+// No `link_count_mismatches` field: [`VfsBackend::link_count`] always
+// computes the count live with `SELECT COUNT(*) FROM fs_dentry WHERE ino =
+// ?` (see `SqliteBackend::link_count`) rather than reading it back from a
+// cached column, so there's no second value it could ever disagree with.
+// A link-count check here would only ever re-derive `dangling_dentries`.
+
+/// Magic bytes opening every archive produced by [`Filesystem::export_archive`].
+const ARCHIVE_MAGIC: &[u8; 8] = b"AGENTFAR";
+/// Archive format version, bumped whenever the index layout changes.
+///
+/// Bumped to 2 when [`ArchiveEntryKind::Special`] and the per-entry `rdev`
+/// field were added; a version-1 reader/writer doesn't know about either.
+const ARCHIVE_VERSION: u32 = 2;
+
+/// The kind of node an [`ArchiveEntry`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveEntryKind {
+    Directory,
+    File,
+    Symlink,
+    /// A device node, FIFO, or socket — anything [`Filesystem::mknod`]
+    /// creates. `rdev` on the owning [`ArchiveEntry`] carries the device
+    /// number back through [`Filesystem::import_archive`]; it's meaningless
+    /// (and `0`) for FIFOs and sockets, same as [`Stats::rdev`].
+    Special,
+}
+
+/// One entry in an archive's index, as produced by [`Filesystem::list_archive`].
+///
+/// `data_offset`/`data_len` locate the entry's raw bytes within the data
+/// region that follows the index (the file's content, or the symlink's
+/// target string); both are `0` for directories and [`ArchiveEntryKind::Special`]
+/// nodes. `rdev` is only meaningful for `Special` entries describing a
+/// character or block device.
+#[derive(Debug, Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub kind: ArchiveEntryKind,
+    pub mode: u32,
+    pub size: i64,
+    pub data_offset: u64,
+    pub data_len: u64,
+    pub rdev: u32,
+}
+
 /// File statistics
 #[derive(Debug, Clone)]
 pub struct Stats {
@@ -29,6 +235,9 @@ pub struct Stats {
     pub atime: i64,
     pub mtime: i64,
     pub ctime: i64,
+    /// Device number, meaningful only when [`Self::is_char_device`] or
+    /// [`Self::is_block_device`] is true.
+    pub rdev: u32,
 }
 
 impl Stats {
@@ -43,33 +252,134 @@ impl Stats {
     pub fn is_symlink(&self) -> bool {
         (self.mode & S_IFMT) == S_IFLNK
     }
+
+    pub fn is_char_device(&self) -> bool {
+        (self.mode & S_IFMT) == S_IFCHR
+    }
+
+    pub fn is_block_device(&self) -> bool {
+        (self.mode & S_IFMT) == S_IFBLK
+    }
+
+    pub fn is_fifo(&self) -> bool {
+        (self.mode & S_IFMT) == S_IFIFO
+    }
+
+    pub fn is_socket(&self) -> bool {
+        (self.mode & S_IFMT) == S_IFSOCK
+    }
+}
+
+/// A bounded path -> inode cache, consulted by `resolve_path` to skip
+/// re-walking `fs_dentry` one component at a time on every lookup.
+///
+/// Eviction is plain LRU: `get` and `put` both move the touched key to the
+/// back of `order`, and `put` evicts the front of `order` once `capacity`
+/// is exceeded. `enabled` lets callers (tests, mostly) turn caching off
+/// entirely to rule it out while debugging a correctness issue.
+struct PathCache {
+    enabled: bool,
+    capacity: usize,
+    entries: HashMap<String, i64>,
+    order: VecDeque<String>,
+}
+
+impl PathCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            enabled: true,
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, path: &str) -> Option<i64> {
+        if !self.enabled {
+            return None;
+        }
+        let ino = *self.entries.get(path)?;
+        self.touch(path);
+        Some(ino)
+    }
+
+    fn put(&mut self, path: String, ino: i64) {
+        if !self.enabled {
+            return;
+        }
+        if self.entries.insert(path.clone(), ino).is_some() {
+            self.touch(&path);
+            return;
+        }
+        self.order.push_back(path);
+        if self.entries.len() > self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.entries.remove(&evicted);
+            }
+        }
+    }
+
+    fn touch(&mut self, path: &str) {
+        if let Some(pos) = self.order.iter().position(|p| p == path) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(path.to_string());
+    }
+
+    /// Drop `path` itself and every entry nested under it (its old
+    /// descendants, if it named a directory), since a single rename or
+    /// removal can invalidate an entire subtree at once.
+    fn invalidate_prefix(&mut self, path: &str) {
+        let prefix = format!("{}/", path.trim_end_matches('/'));
+        self.entries
+            .retain(|key, _| key != path && !key.starts_with(&prefix));
+        self.order.retain(|key| self.entries.contains_key(key));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
 }
 
-/// A filesystem backed by SQLite
+/// A filesystem backed by a pluggable [`VfsBackend`] (SQLite by default).
+///
+/// `Filesystem` owns only path logic: normalizing and splitting paths,
+/// walking `resolve_path`, following symlinks in `stat`, and the path
+/// resolution cache. Every actual storage operation — inode metadata,
+/// directory entries, content-addressed chunk data — goes through
+/// `self.backend`, so a different `VfsBackend` implementor can be plugged
+/// in (an in-memory backend for tests, a remote backend, etc.) without
+/// touching any of this path logic.
 #[derive(Clone)]
 pub struct Filesystem {
-    conn: Arc<Connection>,
+    backend: Arc<dyn VfsBackend>,
     chunk_size: usize,
+    path_cache: Arc<Mutex<PathCache>>,
 }
 
 impl Filesystem {
-    /// Create a new filesystem
+    /// Create a new SQLite-backed filesystem.
     pub async fn new(db_path: &str) -> Result<Self> {
         let db = Builder::new_local(db_path).build().await?;
         let conn = Arc::new(db.connect()?);
         Self::from_connection(conn).await
     }
 
-    /// Create a filesystem from an existing connection
+    /// Create a SQLite-backed filesystem from an existing connection.
     pub async fn from_connection(conn: Arc<Connection>) -> Result<Self> {
-        // Initialize schema first
-        Self::initialize_schema(&conn).await?;
-
-        // Get chunk_size from config (or use default)
-        let chunk_size = Self::read_chunk_size(&conn).await?;
+        let backend = SqliteBackend::new(conn).await?;
+        let chunk_size = Self::read_chunk_size(backend.connection()).await?;
+        Self::with_backend(Arc::new(backend), chunk_size)
+    }
 
-        let fs = Self { conn, chunk_size };
-        Ok(fs)
+    /// Create a filesystem on top of an arbitrary [`VfsBackend`].
+    pub fn with_backend(backend: Arc<dyn VfsBackend>, chunk_size: usize) -> Result<Self> {
+        Ok(Self {
+            backend,
+            chunk_size,
+            path_cache: Arc::new(Mutex::new(PathCache::new(DEFAULT_PATH_CACHE_CAPACITY))),
+        })
     }
 
     /// Get the configured chunk size
@@ -77,109 +387,27 @@ impl Filesystem {
         self.chunk_size
     }
 
-    /// Initialize the database schema
-    async fn initialize_schema(conn: &Connection) -> Result<()> {
-        // Create config table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS fs_config (
-                key TEXT PRIMARY KEY,
-                value TEXT NOT NULL
-            )",
-            (),
-        )
-        .await?;
-
-        // Create inode table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS fs_inode (
-                ino INTEGER PRIMARY KEY AUTOINCREMENT,
-                mode INTEGER NOT NULL,
-                uid INTEGER NOT NULL DEFAULT 0,
-                gid INTEGER NOT NULL DEFAULT 0,
-                size INTEGER NOT NULL DEFAULT 0,
-                atime INTEGER NOT NULL,
-                mtime INTEGER NOT NULL,
-                ctime INTEGER NOT NULL
-            )",
-            (),
-        )
-        .await?;
-
-        // Create directory entry table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS fs_dentry (
-                id INTEGER PRIMARY KEY AUTOINCREMENT,
-                name TEXT NOT NULL,
-                parent_ino INTEGER NOT NULL,
-                ino INTEGER NOT NULL,
-                UNIQUE(parent_ino, name)
-            )",
-            (),
-        )
-        .await?;
-
-        // Create index for efficient path lookups
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_fs_dentry_parent
-            ON fs_dentry(parent_ino, name)",
-            (),
-        )
-        .await?;
-
-        // Create data chunks table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS fs_data (
-                ino INTEGER NOT NULL,
-                chunk_index INTEGER NOT NULL,
-                data BLOB NOT NULL,
-                PRIMARY KEY (ino, chunk_index)
-            )",
-            (),
-        )
-        .await?;
-
-        // Create symlink table
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS fs_symlink (
-                ino INTEGER PRIMARY KEY,
-                target TEXT NOT NULL
-            )",
-            (),
-        )
-        .await?;
-
-        // Ensure chunk_size config exists
-        let mut rows = conn
-            .query("SELECT value FROM fs_config WHERE key = 'chunk_size'", ())
-            .await?;
-
-        if rows.next().await?.is_none() {
-            conn.execute(
-                "INSERT INTO fs_config (key, value) VALUES ('chunk_size', ?)",
-                (DEFAULT_CHUNK_SIZE.to_string(),),
-            )
-            .await?;
-        }
-
-        // Ensure root directory exists
-        let mut rows = conn
-            .query("SELECT ino FROM fs_inode WHERE ino = ?", (ROOT_INO,))
-            .await?;
+    /// Drop every cached path -> inode resolution. Clones of this
+    /// `Filesystem` share the same cache, so this affects all of them.
+    pub fn clear_cache(&self) {
+        self.path_cache.lock().unwrap().clear();
+    }
 
-        if rows.next().await?.is_none() {
-            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-            conn.execute(
-                "INSERT INTO fs_inode (ino, mode, uid, gid, size, atime, mtime, ctime)
-                VALUES (?, ?, 0, 0, 0, ?, ?, ?)",
-                (ROOT_INO, DEFAULT_DIR_MODE as i64, now, now, now),
-            )
-            .await?;
+    /// Enable or disable the path resolution cache. Disabling it is mainly
+    /// useful to rule the cache out while chasing a correctness bug, since
+    /// every `resolve_path` call then always hits `fs_dentry` directly.
+    pub fn set_cache_enabled(&self, enabled: bool) {
+        let mut cache = self.path_cache.lock().unwrap();
+        cache.enabled = enabled;
+        if !enabled {
+            cache.clear();
         }
-
-        Ok(())
     }
 
-    /// Read chunk size from config
+    /// Read the configured chunk size directly out of `fs_config`. This is
+    /// the one place `Filesystem` still queries a raw connection rather
+    /// than going through `VfsBackend`, since `with_backend` callers using a
+    /// non-SQLite backend simply pass their own `chunk_size` in instead.
     async fn read_chunk_size(conn: &Connection) -> Result<usize> {
         let mut rows = conn
             .query("SELECT value FROM fs_config WHERE key = 'chunk_size'", ())
@@ -201,6 +429,264 @@ impl Filesystem {
         }
     }
 
+    /// Release every block a file's chunks point to, without touching the
+    /// chunk entries themselves (the caller deletes those separately).
+    async fn release_file_blocks(&self, ino: i64) -> Result<()> {
+        for hash in self.backend.chunk_hashes(ino).await? {
+            self.backend.release_block(&hash).await?;
+        }
+        Ok(())
+    }
+
+    /// Logical (uncompressed, as read back by `read_file`) vs. physical
+    /// (unique block bytes actually stored on disk, after both dedup and
+    /// per-chunk compression) byte counts across the whole filesystem. See
+    /// [`DedupStats::compression_ratio`] for the combined savings as a
+    /// single number.
+    pub async fn dedup_stats(&self) -> Result<DedupStats> {
+        self.backend.dedup_stats().await
+    }
+
+    /// Scan the store for rows left behind by a crash mid-way through a
+    /// manual cascade delete (orphaned chunks/symlinks, dangling dentries,
+    /// non-contiguous chunk layouts, size/content mismatches). Pass
+    /// `repair: true` to also delete orphans and recompute mismatched
+    /// sizes.
+    pub async fn fsck(&self, repair: bool) -> Result<FsckReport> {
+        self.backend.fsck(repair).await
+    }
+
+    /// Logical file bytes, physical chunk bytes, and chunk count across the
+    /// whole store — the same view [`Filesystem::dedup_stats`] returns,
+    /// under the name callers deciding whether to run [`Filesystem::gc`]
+    /// would look for.
+    pub async fn storage_stats(&self) -> Result<DedupStats> {
+        self.dedup_stats().await
+    }
+
+    /// Delete blocks no longer referenced by any chunk entry — left behind
+    /// by a refcount bug or an interrupted write rather than freed through
+    /// the normal `remove`/overwrite path — and reclaim their on-disk
+    /// pages. Returns the number of physical bytes freed.
+    pub async fn gc(&self) -> Result<i64> {
+        self.backend.gc().await
+    }
+
+    /// Walk every path under the root, breadth-first, pairing each with its
+    /// own (non-symlink-following) stats. Used by [`Self::export_archive`].
+    async fn walk_tree(&self) -> Result<Vec<(String, Stats)>> {
+        let mut found = Vec::new();
+        let mut dirs = VecDeque::new();
+        dirs.push_back("/".to_string());
+
+        while let Some(dir) = dirs.pop_front() {
+            let Some(names) = self.readdir(&dir).await? else {
+                continue;
+            };
+            for name in names {
+                let child = if dir == "/" {
+                    format!("/{name}")
+                } else {
+                    format!("{dir}/{name}")
+                };
+                let stats = self
+                    .lstat(&child)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("{child} vanished during archive export"))?;
+                if stats.is_directory() {
+                    dirs.push_back(child.clone());
+                }
+                found.push((child, stats));
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Serialize the whole tree (directories, files, symlinks) into a single
+    /// self-describing archive, Fuchsia-FAR-style: a sorted index, with one
+    /// fixed-width-field entry per path, followed by a data region holding
+    /// the concatenated bytes the index's entries point into.
+    ///
+    /// A file's content is stored as one contiguous span rather than
+    /// replicating its chunk boundaries — the archive is a portability
+    /// format, not a dump of on-disk layout, and [`Self::import_archive`]
+    /// re-chunks content through the normal write path anyway. Device
+    /// nodes, FIFOs, and sockets round-trip as [`ArchiveEntryKind::Special`]
+    /// entries, carrying `rdev` but no payload; extended attributes aren't
+    /// captured.
+    pub async fn export_archive<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let mut entries = self.walk_tree().await?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        // Collect every entry's payload up front, since the index (written
+        // first) records each one's offset/length into the data region that
+        // follows it.
+        let mut payloads = Vec::with_capacity(entries.len());
+        let mut offset: u64 = 0;
+        for (path, stats) in &entries {
+            let payload = if stats.is_symlink() {
+                self.readlink(path)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("{path} vanished during archive export"))?
+                    .into_bytes()
+            } else if stats.is_file() {
+                self.read_file(path)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("{path} vanished during archive export"))?
+            } else {
+                Vec::new()
+            };
+            let len = payload.len() as u64;
+            payloads.push((offset, len, payload));
+            offset += len;
+        }
+
+        writer.write_all(ARCHIVE_MAGIC)?;
+        writer.write_all(&ARCHIVE_VERSION.to_le_bytes())?;
+        writer.write_all(&(entries.len() as u32).to_le_bytes())?;
+
+        for ((path, stats), (data_offset, data_len, _)) in entries.iter().zip(&payloads) {
+            let is_special = stats.is_char_device()
+                || stats.is_block_device()
+                || stats.is_fifo()
+                || stats.is_socket();
+            let kind: u8 = if stats.is_directory() {
+                0
+            } else if stats.is_symlink() {
+                2
+            } else if is_special {
+                3
+            } else {
+                1
+            };
+            let path_bytes = path.as_bytes();
+            writer.write_all(&(path_bytes.len() as u16).to_le_bytes())?;
+            writer.write_all(path_bytes)?;
+            writer.write_all(&[kind])?;
+            writer.write_all(&stats.mode.to_le_bytes())?;
+            writer.write_all(&stats.size.to_le_bytes())?;
+            writer.write_all(&data_offset.to_le_bytes())?;
+            writer.write_all(&data_len.to_le_bytes())?;
+            writer.write_all(&stats.rdev.to_le_bytes())?;
+        }
+
+        for (_, _, payload) in &payloads {
+            writer.write_all(payload)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read just an archive's index — path, type, mode, size, and the
+    /// offset/length of its bytes in the data region — without reading the
+    /// data region itself.
+    pub fn list_archive<R: Read>(reader: &mut R) -> Result<Vec<ArchiveEntry>> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            anyhow::bail!("Not an agentfs archive");
+        }
+
+        let version = read_u32(reader)?;
+        if version != ARCHIVE_VERSION {
+            anyhow::bail!("Unsupported archive version {version}");
+        }
+
+        let entry_count = read_u32(reader)?;
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let path_len = read_u16(reader)?;
+            let mut path_bytes = vec![0u8; path_len as usize];
+            reader.read_exact(&mut path_bytes)?;
+            let path = String::from_utf8(path_bytes)?;
+
+            let mut kind_byte = [0u8; 1];
+            reader.read_exact(&mut kind_byte)?;
+            let kind = match kind_byte[0] {
+                0 => ArchiveEntryKind::Directory,
+                1 => ArchiveEntryKind::File,
+                2 => ArchiveEntryKind::Symlink,
+                3 => ArchiveEntryKind::Special,
+                other => anyhow::bail!("Unknown archive entry kind {other}"),
+            };
+
+            let mode = read_u32(reader)?;
+            let size = read_i64(reader)?;
+            let data_offset = read_u64(reader)?;
+            let data_len = read_u64(reader)?;
+            let rdev = read_u32(reader)?;
+
+            entries.push(ArchiveEntry {
+                path,
+                kind,
+                mode,
+                size,
+                data_offset,
+                data_len,
+                rdev,
+            });
+        }
+
+        Ok(entries)
+    }
+
+    /// Reconstruct the tree an archive describes into this filesystem,
+    /// rebuilding `fs_inode`/`fs_dentry`/`fs_data`/`fs_symlink` rows for each
+    /// entry (typically into an empty store — an entry whose path already
+    /// exists as a directory fails the same way [`Self::mkdir`] would).
+    /// Runs in one transaction: if any entry fails to import, or the
+    /// archive is truncated, every write is rolled back and the store is
+    /// left exactly as it was before the call.
+    pub async fn import_archive<R: Read>(&self, reader: &mut R) -> Result<()> {
+        let entries = Self::list_archive(reader)?;
+
+        let mut payloads = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            let mut payload = vec![0u8; entry.data_len as usize];
+            reader.read_exact(&mut payload)?;
+            payloads.push(payload);
+        }
+
+        self.backend.begin().await?;
+        match self.import_entries(&entries, &payloads).await {
+            Ok(()) => {
+                self.backend.commit().await?;
+                Ok(())
+            }
+            Err(err) => {
+                self.backend.rollback().await?;
+                // Some of the failed import's writes may already have been
+                // cached before the rollback undid them.
+                self.path_cache.lock().unwrap().clear();
+                Err(err)
+            }
+        }
+    }
+
+    async fn import_entries(&self, entries: &[ArchiveEntry], payloads: &[Vec<u8>]) -> Result<()> {
+        for (entry, payload) in entries.iter().zip(payloads) {
+            match entry.kind {
+                ArchiveEntryKind::Directory => {
+                    self.mkdir(&entry.path).await?;
+                    self.chmod(&entry.path, entry.mode).await?;
+                }
+                ArchiveEntryKind::File => {
+                    self.write_file(&entry.path, payload).await?;
+                    self.chmod(&entry.path, entry.mode).await?;
+                }
+                ArchiveEntryKind::Symlink => {
+                    let target = String::from_utf8(payload.clone())?;
+                    self.symlink(&target, &entry.path).await?;
+                }
+                ArchiveEntryKind::Special => {
+                    self.mknod(&entry.path, entry.mode, entry.rdev).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Normalize a path
     fn normalize_path(&self, path: &str) -> String {
         let normalized = path.trim_end_matches('/');
@@ -254,106 +740,50 @@ impl Filesystem {
             .collect()
     }
 
-    /// Get link count for an inode
-    async fn get_link_count(&self, ino: i64) -> Result<u32> {
-        let mut rows = self
-            .conn
-            .query(
-                "SELECT COUNT(*) as count FROM fs_dentry WHERE ino = ?",
-                (ino,),
-            )
-            .await?;
-
-        if let Some(row) = rows.next().await? {
-            let count = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0);
-            Ok(count as u32)
-        } else {
-            Ok(0)
-        }
-    }
-
-    /// Build a Stats object from a database row
-    ///
-    /// The row should contain columns in this order:
-    /// ino, mode, uid, gid, size, atime, mtime, ctime
-    async fn build_stats_from_row(&self, row: &turso::Row, ino: i64) -> Result<Stats> {
-        let nlink = self.get_link_count(ino).await?;
-        Ok(Stats {
-            ino,
-            mode: row
-                .get_value(1)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0) as u32,
-            nlink,
-            uid: row
-                .get_value(2)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0) as u32,
-            gid: row
-                .get_value(3)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0) as u32,
-            size: row
-                .get_value(4)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0),
-            atime: row
-                .get_value(5)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0),
-            mtime: row
-                .get_value(6)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0),
-            ctime: row
-                .get_value(7)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0),
-        })
-    }
-
-    /// Resolve a path to an inode number
+    /// Resolve a path to an inode number, consulting and populating the
+    /// path resolution cache so repeated lookups of the same (or a
+    /// deeply-nested) path skip re-walking the backend one component at a
+    /// time.
     async fn resolve_path(&self, path: &str) -> Result<Option<i64>> {
-        let components = self.split_path(path);
-        if components.is_empty() {
+        let normalized = self.normalize_path(path);
+        if normalized == "/" {
             return Ok(Some(ROOT_INO));
         }
 
+        if let Some(ino) = self.path_cache.lock().unwrap().get(&normalized) {
+            return Ok(Some(ino));
+        }
+
+        let components = self.split_path(&normalized);
         let mut current_ino = ROOT_INO;
         for component in components {
-            let mut rows = self
-                .conn
-                .query(
-                    "SELECT ino FROM fs_dentry WHERE parent_ino = ? AND name = ?",
-                    (current_ino, component.as_str()),
-                )
-                .await?;
-
-            if let Some(row) = rows.next().await? {
-                current_ino = row
-                    .get_value(0)
-                    .ok()
-                    .and_then(|v| v.as_integer().copied())
-                    .unwrap_or(0);
-            } else {
-                return Ok(None);
+            match self.backend.lookup(current_ino, &component).await? {
+                Some(ino) => current_ino = ino,
+                None => return Ok(None),
             }
         }
 
+        self.path_cache.lock().unwrap().put(normalized, current_ino);
         Ok(Some(current_ino))
     }
 
+    /// Build a [`Stats`] from an inode's backend-agnostic metadata record.
+    async fn build_stats(&self, ino: i64, record: crate::backend::InodeRecord) -> Result<Stats> {
+        let nlink = self.backend.link_count(ino).await?;
+        Ok(Stats {
+            ino,
+            mode: record.mode,
+            nlink,
+            uid: record.uid,
+            gid: record.gid,
+            size: record.size,
+            atime: record.atime,
+            mtime: record.mtime,
+            ctime: record.ctime,
+            rdev: record.rdev,
+        })
+    }
+
     /// Get file statistics without following symlinks
     pub async fn lstat(&self, path: &str) -> Result<Option<Stats>> {
         let path = self.normalize_path(path);
@@ -362,25 +792,9 @@ impl Filesystem {
             None => return Ok(None),
         };
 
-        let mut rows = self
-            .conn
-            .query(
-                "SELECT ino, mode, uid, gid, size, atime, mtime, ctime FROM fs_inode WHERE ino = ?",
-                (ino,),
-            )
-            .await?;
-
-        if let Some(row) = rows.next().await? {
-            let ino_val = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0);
-
-            let stats = self.build_stats_from_row(&row, ino_val).await?;
-            Ok(Some(stats))
-        } else {
-            Ok(None)
+        match self.backend.get_inode(ino).await? {
+            Some(record) => Ok(Some(self.build_stats(ino, record).await?)),
+            None => Ok(None),
         }
     }
 
@@ -398,117 +812,159 @@ impl Filesystem {
                 None => return Ok(None),
             };
 
-            let mut rows = self
-                .conn
-                .query(
-                    "SELECT ino, mode, uid, gid, size, atime, mtime, ctime FROM fs_inode WHERE ino = ?",
-                    (ino,),
-                )
-                .await?;
-
-            if let Some(row) = rows.next().await? {
-                let ino_val = row
-                    .get_value(0)
-                    .ok()
-                    .and_then(|v| v.as_integer().copied())
-                    .unwrap_or(0);
-
-                let mode = row
-                    .get_value(1)
-                    .ok()
-                    .and_then(|v| v.as_integer().copied())
-                    .unwrap_or(0) as u32;
-
-                // Check if this is a symlink
-                if (mode & S_IFMT) == S_IFLNK {
-                    // Read the symlink target
-                    let target = self
-                        .readlink(&current_path)
-                        .await?
-                        .ok_or_else(|| anyhow::anyhow!("Symlink has no target"))?;
-
-                    // Resolve target path (handle both absolute and relative paths)
-                    current_path = if target.starts_with('/') {
-                        target
-                    } else {
-                        // Relative path - resolve relative to the symlink's directory
-                        let base_path = Path::new(&current_path);
-                        let parent = base_path.parent().unwrap_or(Path::new("/"));
-                        let joined = parent.join(&target);
-                        joined.to_string_lossy().into_owned()
-                    };
-                    current_path = self.normalize_path(&current_path);
-                    continue; // Follow the symlink
-                }
-
-                // Not a symlink, return the stats
-                let stats = self.build_stats_from_row(&row, ino_val).await?;
-                return Ok(Some(stats));
-            } else {
+            let Some(record) = self.backend.get_inode(ino).await? else {
                 return Ok(None);
+            };
+
+            // Check if this is a symlink
+            if (record.mode & S_IFMT) == S_IFLNK {
+                // Read the symlink target
+                let target = self
+                    .readlink(&current_path)
+                    .await?
+                    .ok_or_else(|| anyhow::anyhow!("Symlink has no target"))?;
+
+                // Resolve target path (handle both absolute and relative paths)
+                current_path = if target.starts_with('/') {
+                    target
+                } else {
+                    // Relative path - resolve relative to the symlink's directory
+                    let base_path = Path::new(&current_path);
+                    let parent = base_path.parent().unwrap_or(Path::new("/"));
+                    let joined = parent.join(&target);
+                    joined.to_string_lossy().into_owned()
+                };
+                current_path = self.normalize_path(&current_path);
+                continue; // Follow the symlink
             }
+
+            // Not a symlink, return the stats
+            return Ok(Some(self.build_stats(ino, record).await?));
         }
 
         // Too many symlinks
         anyhow::bail!("Too many levels of symbolic links")
     }
 
-    /// Create a directory
-    pub async fn mkdir(&self, path: &str) -> Result<()> {
-        let path = self.normalize_path(path);
-        let components = self.split_path(&path);
+    /// Change an inode's permission bits, preserving its `S_IFMT` type bits.
+    pub async fn chmod(&self, path: &str, mode: u32) -> Result<()> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
 
-        if components.is_empty() {
-            anyhow::bail!("Cannot create root directory");
-        }
+        let record = self
+            .backend
+            .get_inode(ino)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
 
-        let parent_path = if components.len() == 1 {
-            "/".to_string()
-        } else {
-            format!("/{}", components[..components.len() - 1].join("/"))
-        };
+        let new_mode = (record.mode & S_IFMT) | (mode & !S_IFMT);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.backend.update_mode(ino, new_mode, now).await?;
+        Ok(())
+    }
 
-        let parent_ino = self
-            .resolve_path(&parent_path)
+    /// Change an inode's owning `uid`/`gid`.
+    pub async fn chown(&self, path: &str, uid: u32, gid: u32) -> Result<()> {
+        let ino = self
+            .resolve_path(path)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("Parent directory does not exist"))?;
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
 
-        let name = components.last().unwrap();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.backend.update_owner(ino, uid, gid, now).await?;
+        Ok(())
+    }
 
-        // Check if already exists
-        if (self.resolve_path(&path).await?).is_some() {
-            anyhow::bail!("Directory already exists");
-        }
+    /// Change an inode's `atime`/`mtime`.
+    pub async fn utimens(&self, path: &str, atime: i64, mtime: i64) -> Result<()> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
 
-        // Create inode
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-        self.conn
-            .execute(
-                "INSERT INTO fs_inode (mode, uid, gid, size, atime, mtime, ctime)
-                VALUES (?, 0, 0, 0, ?, ?, ?)",
-                (DEFAULT_DIR_MODE as i64, now, now, now),
-            )
-            .await?;
-
-        let mut rows = self.conn.query("SELECT last_insert_rowid()", ()).await?;
-        let ino = if let Some(row) = rows.next().await? {
-            row.get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .ok_or_else(|| anyhow::anyhow!("Failed to get inode"))?
-        } else {
-            anyhow::bail!("Failed to get inode");
-        };
+        self.backend.update_times(ino, atime, mtime, now).await?;
+        Ok(())
+    }
 
-        // Create directory entry
-        self.conn
-            .execute(
-                "INSERT INTO fs_dentry (name, parent_ino, ino) VALUES (?, ?, ?)",
-                (name.as_str(), parent_ino, ino),
-            )
-            .await?;
+    /// Set an extended attribute on `path`, overwriting any existing value.
+    pub async fn setxattr(&self, path: &str, name: &str, value: &[u8]) -> Result<()> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
 
-        Ok(())
+        self.backend.set_xattr(ino, name, value).await
+    }
+
+    /// Fetch an extended attribute's value, if set.
+    pub async fn getxattr(&self, path: &str, name: &str) -> Result<Option<Vec<u8>>> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
+
+        self.backend.get_xattr(ino, name).await
+    }
+
+    /// List the names of every extended attribute set on `path`.
+    pub async fn listxattr(&self, path: &str) -> Result<Vec<String>> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
+
+        self.backend.list_xattrs(ino).await
+    }
+
+    /// Remove an extended attribute from `path`. A no-op if it wasn't set.
+    pub async fn removexattr(&self, path: &str, name: &str) -> Result<()> {
+        let ino = self
+            .resolve_path(path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
+
+        self.backend.remove_xattr(ino, name).await
+    }
+
+    /// Create a directory
+    pub async fn mkdir(&self, path: &str) -> Result<()> {
+        let path = self.normalize_path(path);
+        let components = self.split_path(&path);
+
+        if components.is_empty() {
+            anyhow::bail!("Cannot create root directory");
+        }
+
+        let parent_path = if components.len() == 1 {
+            "/".to_string()
+        } else {
+            format!("/{}", components[..components.len() - 1].join("/"))
+        };
+
+        let parent_ino = self
+            .resolve_path(&parent_path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Parent directory does not exist"))?;
+
+        let name = components.last().unwrap();
+
+        // Check if already exists
+        if (self.resolve_path(&path).await?).is_some() {
+            anyhow::bail!("Directory already exists");
+        }
+
+        let ino = self
+            .backend
+            .create_inode(DEFAULT_DIR_MODE, 0, 0, 0, 0)
+            .await?;
+        self.backend.insert_dentry(parent_ino, name, ino).await?;
+
+        self.path_cache.lock().unwrap().put(path, ino);
+
+        Ok(())
     }
 
     /// Write data to a file
@@ -535,62 +991,37 @@ impl Filesystem {
 
         // Check if file exists
         let ino = if let Some(ino) = self.resolve_path(&path).await? {
-            // Delete existing data
-            self.conn
-                .execute("DELETE FROM fs_data WHERE ino = ?", (ino,))
-                .await?;
+            // Release the blocks the old contents pointed to, then drop the
+            // chunk entries themselves.
+            self.release_file_blocks(ino).await?;
+            self.backend.delete_all_chunks(ino).await?;
             ino
         } else {
-            // Create new inode
-            let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-            self.conn
-                .execute(
-                    "INSERT INTO fs_inode (mode, uid, gid, size, atime, mtime, ctime)
-                    VALUES (?, 0, 0, ?, ?, ?, ?)",
-                    (DEFAULT_FILE_MODE as i64, data.len() as i64, now, now, now),
-                )
-                .await?;
-
-            let mut rows = self.conn.query("SELECT last_insert_rowid()", ()).await?;
-            let ino = if let Some(row) = rows.next().await? {
-                row.get_value(0)
-                    .ok()
-                    .and_then(|v| v.as_integer().copied())
-                    .ok_or_else(|| anyhow::anyhow!("Failed to get inode"))?
-            } else {
-                anyhow::bail!("Failed to get inode");
-            };
-
-            // Create directory entry
-            self.conn
-                .execute(
-                    "INSERT INTO fs_dentry (name, parent_ino, ino) VALUES (?, ?, ?)",
-                    (name.as_str(), parent_ino, ino),
-                )
+            let ino = self
+                .backend
+                .create_inode(DEFAULT_FILE_MODE, 0, 0, data.len() as i64, 0)
                 .await?;
-
+            self.backend.insert_dentry(parent_ino, name, ino).await?;
+            self.path_cache.lock().unwrap().put(path.clone(), ino);
             ino
         };
 
-        // Write data in chunks
+        // Split into content-defined chunks and store each one's bytes
+        // content-addressed, so identical chunks across files (or across
+        // versions of the same file) share a single on-disk copy.
         if !data.is_empty() {
-            for (chunk_index, chunk) in data.chunks(self.chunk_size).enumerate() {
-                self.conn
-                    .execute(
-                        "INSERT INTO fs_data (ino, chunk_index, data) VALUES (?, ?, ?)",
-                        (ino, chunk_index as i64, chunk),
-                    )
+            for (chunk_index, chunk) in cdc_chunks(data).into_iter().enumerate() {
+                let hash = self.backend.put_block(chunk).await?;
+                self.backend
+                    .insert_chunk(ino, chunk_index as i64, &hash)
                     .await?;
             }
         }
 
         // Update size and mtime
         let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
-        self.conn
-            .execute(
-                "UPDATE fs_inode SET size = ?, mtime = ? WHERE ino = ?",
-                (data.len() as i64, now, ino),
-            )
+        self.backend
+            .update_inode_size(ino, data.len() as i64, now)
             .await?;
 
         Ok(())
@@ -603,22 +1034,172 @@ impl Filesystem {
             None => return Ok(None),
         };
 
-        let mut rows = self
-            .conn
-            .query(
-                "SELECT data FROM fs_data WHERE ino = ? ORDER BY chunk_index",
-                (ino,),
-            )
+        Ok(Some(self.backend.read_file_data(ino).await?))
+    }
+
+    /// Read `len` bytes starting at `offset`. The read is assembled from
+    /// whichever content-defined chunks overlap `[offset, offset + len)`;
+    /// any part of the requested range past the file's stored data reads
+    /// back as zeros.
+    pub async fn read_at(&self, path: &str, offset: usize, len: usize) -> Result<Option<Vec<u8>>> {
+        let ino = match self.resolve_path(path).await? {
+            Some(ino) => ino,
+            None => return Ok(None),
+        };
+
+        if len == 0 {
+            return Ok(Some(Vec::new()));
+        }
+
+        let layout = self.backend.chunk_layout(ino).await?;
+        let read_end = offset + len;
+
+        let mut result = Vec::with_capacity(len);
+        let mut pos = 0usize;
+        for (_, hash, chunk_len) in &layout {
+            let chunk_start = pos;
+            let chunk_end = pos + chunk_len;
+            pos = chunk_end;
+            if chunk_end <= offset || chunk_start >= read_end {
+                continue;
+            }
+            let data = self.backend.block_data(hash).await?.unwrap_or_default();
+            let start = offset.max(chunk_start) - chunk_start;
+            let end = read_end.min(chunk_end) - chunk_start;
+            result.extend_from_slice(&data[start..end]);
+        }
+
+        // Anything requested past the last stored chunk (a sparse tail, or
+        // simply past EOF) reads back as zeros.
+        if pos < read_end {
+            result.resize(result.len() + (read_end - pos.max(offset)), 0);
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Write `data` at `offset`. Chunks already overlapping the write are
+    /// patched in place and re-hashed under their existing `chunk_index`;
+    /// bytes past the current end of file (plus any zero-filled gap, for a
+    /// sparse write) are appended as new content-defined chunks.
+    pub async fn write_at(&self, path: &str, offset: usize, data: &[u8]) -> Result<()> {
+        let path = self.normalize_path(path);
+        let ino = self
+            .resolve_path(&path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
+
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        let layout = self.backend.chunk_layout(ino).await?;
+        let write_end = offset + data.len();
+
+        let mut pos = 0usize;
+        let mut next_chunk_index = 0i64;
+        for (chunk_index, hash, chunk_len) in &layout {
+            next_chunk_index = next_chunk_index.max(chunk_index + 1);
+            let chunk_start = pos;
+            let chunk_end = pos + chunk_len;
+            pos = chunk_end;
+            if chunk_end <= offset || chunk_start >= write_end {
+                continue;
+            }
+
+            let mut bytes = self.backend.block_data(hash).await?.unwrap_or_default();
+            let patch_start = offset.max(chunk_start) - chunk_start;
+            let patch_end = write_end.min(chunk_end) - chunk_start;
+            if bytes.len() < patch_end {
+                bytes.resize(patch_end, 0);
+            }
+            let src_start = chunk_start.max(offset) - offset;
+            let src_end = src_start + (patch_end - patch_start);
+            bytes[patch_start..patch_end].copy_from_slice(&data[src_start..src_end]);
+
+            self.backend.release_block(hash).await?;
+            let new_hash = self.backend.put_block(&bytes).await?;
+            self.backend
+                .update_chunk_hash(ino, *chunk_index, &new_hash)
+                .await?;
+        }
+
+        let total = pos;
+        if write_end > total {
+            let mut tail = vec![0u8; offset.saturating_sub(total)];
+            tail.extend_from_slice(&data[total.max(offset) - offset..]);
+            for chunk in cdc_chunks(&tail) {
+                let hash = self.backend.put_block(chunk).await?;
+                self.backend
+                    .insert_chunk(ino, next_chunk_index, &hash)
+                    .await?;
+                next_chunk_index += 1;
+            }
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.backend
+            .update_inode_size(ino, total.max(write_end) as i64, now)
             .await?;
 
-        let mut data = Vec::new();
-        while let Some(row) = rows.next().await? {
-            if let Ok(Value::Blob(chunk)) = row.get_value(0) {
-                data.extend_from_slice(&chunk);
+        Ok(())
+    }
+
+    /// Truncate a file to `new_size`. Chunks entirely beyond the new end are
+    /// dropped, a chunk straddling the new end is trimmed and re-hashed, and
+    /// growing the file appends the zero-filled gap as new content-defined
+    /// chunks.
+    pub async fn truncate(&self, path: &str, new_size: usize) -> Result<()> {
+        let path = self.normalize_path(path);
+        let ino = self
+            .resolve_path(&path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Path does not exist"))?;
+
+        let layout = self.backend.chunk_layout(ino).await?;
+        let mut pos = 0usize;
+        for (chunk_index, hash, chunk_len) in &layout {
+            let chunk_start = pos;
+            let chunk_end = pos + chunk_len;
+            pos = chunk_end;
+
+            if chunk_start >= new_size {
+                self.backend.release_block(hash).await?;
+                self.backend.delete_chunk(ino, *chunk_index).await?;
+            } else if chunk_end > new_size {
+                let keep = new_size - chunk_start;
+                if let Some(mut bytes) = self.backend.block_data(hash).await? {
+                    if bytes.len() > keep {
+                        bytes.truncate(keep);
+                        self.backend.release_block(hash).await?;
+                        let new_hash = self.backend.put_block(&bytes).await?;
+                        self.backend
+                            .update_chunk_hash(ino, *chunk_index, &new_hash)
+                            .await?;
+                    }
+                }
+            }
+        }
+
+        let total: usize = layout.iter().map(|(_, _, len)| *len).sum();
+        if new_size > total {
+            let gap = vec![0u8; new_size - total];
+            let mut next_chunk_index = layout.last().map(|(i, _, _)| i + 1).unwrap_or(0);
+            for chunk in cdc_chunks(&gap) {
+                let hash = self.backend.put_block(chunk).await?;
+                self.backend
+                    .insert_chunk(ino, next_chunk_index, &hash)
+                    .await?;
+                next_chunk_index += 1;
             }
         }
 
-        Ok(Some(data))
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        self.backend
+            .update_inode_size(ino, new_size as i64, now)
+            .await?;
+
+        Ok(())
     }
 
     /// List directory contents
@@ -628,33 +1209,7 @@ impl Filesystem {
             None => return Ok(None),
         };
 
-        let mut rows = self
-            .conn
-            .query(
-                "SELECT name FROM fs_dentry WHERE parent_ino = ? ORDER BY name",
-                (ino,),
-            )
-            .await?;
-
-        let mut entries = Vec::new();
-        while let Some(row) = rows.next().await? {
-            let name = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| {
-                    if let Value::Text(s) = v {
-                        Some(s.clone())
-                    } else {
-                        None
-                    }
-                })
-                .unwrap_or_default();
-            if !name.is_empty() {
-                entries.push(name);
-            }
-        }
-
-        Ok(Some(entries))
+        Ok(Some(self.backend.list_dentries(ino).await?))
     }
 
     /// Create a symbolic link
@@ -686,49 +1241,16 @@ impl Filesystem {
         }
 
         // Create inode for symlink
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs() as i64;
-
         let mode = S_IFLNK | 0o777; // Symlinks typically have 777 permissions
         let size = target.len() as i64;
 
-        self.conn
-            .execute(
-                "INSERT INTO fs_inode (mode, uid, gid, size, atime, mtime, ctime)
-                 VALUES (?, 0, 0, ?, ?, ?, ?)",
-                (mode, size, now, now, now),
-            )
-            .await?;
-
-        // Get the newly created inode
-        let mut rows = self.conn.query("SELECT last_insert_rowid()", ()).await?;
-
-        let ino = if let Some(row) = rows.next().await? {
-            row.get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0)
-        } else {
-            anyhow::bail!("Failed to get new inode");
-        };
-
-        // Store symlink target
-        self.conn
-            .execute(
-                "INSERT INTO fs_symlink (ino, target) VALUES (?, ?)",
-                (ino, target),
-            )
+        let ino = self.backend.create_inode(mode, 0, 0, size, 0).await?;
+        self.backend.set_symlink(ino, target).await?;
+        self.backend
+            .insert_dentry(parent_ino, name.as_str(), ino)
             .await?;
 
-        // Create directory entry
-        self.conn
-            .execute(
-                "INSERT INTO fs_dentry (name, parent_ino, ino) VALUES (?, ?, ?)",
-                (name.as_str(), parent_ino, ino),
-            )
-            .await?;
+        self.path_cache.lock().unwrap().put(linkpath, ino);
 
         Ok(())
     }
@@ -743,45 +1265,63 @@ impl Filesystem {
         };
 
         // Check if it's a symlink by querying the inode
-        let mut rows = self
-            .conn
-            .query("SELECT mode FROM fs_inode WHERE ino = ?", (ino,))
-            .await?;
+        match self.backend.get_inode(ino).await? {
+            Some(record) if (record.mode & S_IFMT) == S_IFLNK => {}
+            Some(_) => anyhow::bail!("Not a symbolic link"),
+            None => return Ok(None),
+        }
 
-        if let Some(row) = rows.next().await? {
-            let mode = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0) as u32;
+        Ok(self.backend.get_symlink(ino).await?)
+    }
 
-            // Check if it's a symlink
-            if (mode & S_IFMT) != S_IFLNK {
-                anyhow::bail!("Not a symbolic link");
-            }
-        } else {
-            return Ok(None);
+    /// Create a device node, FIFO, or socket at `path`. `mode` must carry one
+    /// of the `S_IFCHR`/`S_IFBLK`/`S_IFIFO`/`S_IFSOCK` type bits plus the
+    /// desired permission bits; `rdev` is only meaningful for the two device
+    /// types and is ignored otherwise. Unlike regular files, the new inode
+    /// stores no chunks.
+    pub async fn mknod(&self, path: &str, mode: u32, rdev: u32) -> Result<()> {
+        let file_type = mode & S_IFMT;
+        if ![S_IFCHR, S_IFBLK, S_IFIFO, S_IFSOCK].contains(&file_type) {
+            anyhow::bail!("mknod mode must be a device, FIFO, or socket type");
         }
 
-        // Read target from fs_symlink table
-        let mut rows = self
-            .conn
-            .query("SELECT target FROM fs_symlink WHERE ino = ?", (ino,))
-            .await?;
+        let path = self.normalize_path(path);
+        let components = self.split_path(&path);
 
-        if let Some(row) = rows.next().await? {
-            let target = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| match v {
-                    Value::Text(s) => Some(s.to_string()),
-                    _ => None,
-                })
-                .ok_or_else(|| anyhow::anyhow!("Invalid symlink target"))?;
-            Ok(Some(target))
+        if components.is_empty() {
+            anyhow::bail!("Cannot create node at root");
+        }
+
+        let parent_path = if components.len() == 1 {
+            "/".to_string()
         } else {
-            Ok(None)
+            format!("/{}", components[..components.len() - 1].join("/"))
+        };
+
+        let parent_ino = self
+            .resolve_path(&parent_path)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Parent directory does not exist"))?;
+
+        let name = components.last().unwrap();
+
+        if (self.resolve_path(&path).await?).is_some() {
+            anyhow::bail!("Path already exists");
         }
+
+        let rdev = if file_type == S_IFCHR || file_type == S_IFBLK {
+            rdev
+        } else {
+            0
+        };
+        let ino = self.backend.create_inode(mode, 0, 0, 0, rdev).await?;
+        self.backend
+            .insert_dentry(parent_ino, name.as_str(), ino)
+            .await?;
+
+        self.path_cache.lock().unwrap().put(path, ino);
+
+        Ok(())
     }
 
     /// Remove a file or empty directory
@@ -803,23 +1343,8 @@ impl Filesystem {
         }
 
         // Check if directory is empty
-        let mut rows = self
-            .conn
-            .query(
-                "SELECT COUNT(*) FROM fs_dentry WHERE parent_ino = ?",
-                (ino,),
-            )
-            .await?;
-
-        if let Some(row) = rows.next().await? {
-            let count = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0);
-            if count > 0 {
-                anyhow::bail!("Directory not empty");
-            }
+        if self.backend.dentry_count(ino).await? > 0 {
+            anyhow::bail!("Directory not empty");
         }
 
         // Get parent directory and name
@@ -837,53 +1362,40 @@ impl Filesystem {
         let name = components.last().unwrap();
 
         // Delete the specific directory entry (not all entries pointing to this inode)
-        self.conn
-            .execute(
-                "DELETE FROM fs_dentry WHERE parent_ino = ? AND name = ?",
-                (parent_ino, name.as_str()),
-            )
+        self.backend
+            .remove_dentry(parent_ino, name.as_str())
             .await?;
 
         // Check if this was the last link to the inode
-        let link_count = self.get_link_count(ino).await?;
+        let link_count = self.backend.link_count(ino).await?;
         if link_count == 0 {
             // Manually handle cascading deletes since we don't use foreign keys
-            // Delete data blocks
-            self.conn
-                .execute("DELETE FROM fs_data WHERE ino = ?", (ino,))
-                .await?;
+            // Release the content-addressed blocks this file's chunks
+            // pointed to, then delete the chunk rows themselves.
+            self.release_file_blocks(ino).await?;
+            self.backend.delete_all_chunks(ino).await?;
 
             // Delete symlink if exists
-            self.conn
-                .execute("DELETE FROM fs_symlink WHERE ino = ?", (ino,))
-                .await?;
+            self.backend.delete_symlink(ino).await?;
+
+            // Delete extended attributes
+            self.backend.delete_xattrs(ino).await?;
 
             // Delete inode
-            self.conn
-                .execute("DELETE FROM fs_inode WHERE ino = ?", (ino,))
-                .await?;
+            self.backend.delete_inode(ino).await?;
         }
 
+        // Drop the removed path, and any cached descendants if it named a
+        // directory, so a later lookup can't return the stale inode.
+        self.path_cache.lock().unwrap().invalidate_prefix(&path);
+
         Ok(())
     }
 
     /// Get the number of chunks for a given inode (for testing)
     #[cfg(test)]
     async fn get_chunk_count(&self, ino: i64) -> Result<i64> {
-        let mut rows = self
-            .conn
-            .query("SELECT COUNT(*) FROM fs_data WHERE ino = ?", (ino,))
-            .await?;
-
-        if let Some(row) = rows.next().await? {
-            Ok(row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(0))
-        } else {
-            Ok(0)
-        }
+        Ok(self.backend.chunk_layout(ino).await?.len() as i64)
     }
 }
 
@@ -900,6 +1412,13 @@ mod tests {
     }
 
     // ==================== Chunk Size Boundary Tests ====================
+    //
+    // `write_file` now splits content-defined chunks (see `cdc_chunks`)
+    // rather than fixed `chunk_size` slices, so the chunk *count* for a
+    // given input is data-dependent instead of a deterministic function of
+    // its length. These tests assert the bounds CDC guarantees (at least
+    // one chunk, no chunk over `CDC_MAX_CHUNK_SIZE`) plus byte-for-byte
+    // roundtrip correctness, rather than an exact chunk count.
 
     #[tokio::test]
     async fn test_file_smaller_than_chunk_size() -> Result<()> {
@@ -914,7 +1433,7 @@ mod tests {
         assert_eq!(read_data.len(), 100);
         assert_eq!(read_data, data);
 
-        // Verify only 1 chunk was created
+        // Below the CDC minimum chunk size, so it must fit in a single chunk.
         let ino = fs.resolve_path("/small.txt").await?.unwrap();
         let chunk_count = fs.get_chunk_count(ino).await?;
         assert_eq!(chunk_count, 1);
@@ -936,10 +1455,9 @@ mod tests {
         assert_eq!(read_data.len(), chunk_size);
         assert_eq!(read_data, data);
 
-        // Verify only 1 chunk was created
         let ino = fs.resolve_path("/exact.txt").await?.unwrap();
         let chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(chunk_count, 1);
+        assert!(chunk_count >= 1);
 
         Ok(())
     }
@@ -958,10 +1476,9 @@ mod tests {
         assert_eq!(read_data.len(), chunk_size + 1);
         assert_eq!(read_data, data);
 
-        // Verify 2 chunks were created
         let ino = fs.resolve_path("/overflow.txt").await?.unwrap();
         let chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(chunk_count, 2);
+        assert!(chunk_count >= 1);
 
         Ok(())
     }
@@ -981,10 +1498,13 @@ mod tests {
         assert_eq!(read_data.len(), data_size);
         assert_eq!(read_data, data);
 
-        // Verify 3 chunks were created
+        // CDC never produces a chunk bigger than CDC_MAX_CHUNK_SIZE, so a
+        // ~10KiB file (well under that) must still be split into at least
+        // one chunk, and no individual chunk may exceed the max.
         let ino = fs.resolve_path("/multi.txt").await?.unwrap();
         let chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(chunk_count, 3);
+        assert!(chunk_count >= 1);
+        assert!((data_size as i64).div_ceil(CDC_MAX_CHUNK_SIZE as i64) <= chunk_count);
 
         Ok(())
     }
@@ -1087,24 +1607,21 @@ mod tests {
 
         let chunk_size = fs.chunk_size();
 
-        // Write initial large file (3 chunks)
+        // Write initial large file
         let initial_data: Vec<u8> = (0..chunk_size * 3).map(|i| (i % 256) as u8).collect();
         fs.write_file("/overwrite.txt", &initial_data).await?;
 
         let ino = fs.resolve_path("/overwrite.txt").await?.unwrap();
-        let initial_chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(initial_chunk_count, 3);
+        assert!(fs.get_chunk_count(ino).await? >= 1);
 
-        // Overwrite with smaller file (1 chunk)
+        // Overwrite with a single-chunk file
         let new_data = vec![42u8; 100];
         fs.write_file("/overwrite.txt", &new_data).await?;
 
         // Verify old chunks are gone and new data is correct
         let read_data = fs.read_file("/overwrite.txt").await?.unwrap();
         assert_eq!(read_data, new_data);
-
-        let new_chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(new_chunk_count, 1);
+        assert_eq!(fs.get_chunk_count(ino).await?, 1);
 
         // Verify size is updated
         let stats = fs.stat("/overwrite.txt").await?.unwrap();
@@ -1126,14 +1643,14 @@ mod tests {
         let ino = fs.resolve_path("/grow.txt").await?.unwrap();
         assert_eq!(fs.get_chunk_count(ino).await?, 1);
 
-        // Overwrite with larger file (3 chunks)
+        // Overwrite with a larger file
         let new_data: Vec<u8> = (0..chunk_size * 3).map(|i| (i % 256) as u8).collect();
         fs.write_file("/grow.txt", &new_data).await?;
 
         // Verify data is correct
         let read_data = fs.read_file("/grow.txt").await?.unwrap();
         assert_eq!(read_data, new_data);
-        assert_eq!(fs.get_chunk_count(ino).await?, 3);
+        assert!(fs.get_chunk_count(ino).await? >= 1);
 
         Ok(())
     }
@@ -1151,12 +1668,12 @@ mod tests {
         assert_eq!(read_data.len(), data_size);
         assert_eq!(read_data, data);
 
-        // Verify correct number of chunks
-        let chunk_size = fs.chunk_size();
-        let expected_chunks = (data_size + chunk_size - 1) / chunk_size;
+        // No chunk may exceed the CDC maximum, so a 1MB file must be split
+        // into at least that many chunks.
+        let min_expected_chunks = data_size.div_ceil(CDC_MAX_CHUNK_SIZE);
         let ino = fs.resolve_path("/large.bin").await?.unwrap();
         let actual_chunks = fs.get_chunk_count(ino).await? as usize;
-        assert_eq!(actual_chunks, expected_chunks);
+        assert!(actual_chunks >= min_expected_chunks);
 
         Ok(())
     }
@@ -1180,38 +1697,12 @@ mod tests {
         let chunk_size = fs.chunk_size();
         assert!(chunk_size > 0);
 
-        // Write data and verify chunks match expected based on chunk_size
         let data = vec![0u8; chunk_size * 2 + 1];
         fs.write_file("/test.bin", &data).await?;
 
         let ino = fs.resolve_path("/test.bin").await?.unwrap();
         let chunk_count = fs.get_chunk_count(ino).await?;
-        assert_eq!(chunk_count, 3);
-
-        Ok(())
-    }
-
-    #[tokio::test]
-    async fn test_config_persistence() -> Result<()> {
-        let (fs, _dir) = create_test_fs().await?;
-
-        // Query fs_config table directly
-        let mut rows = fs
-            .conn
-            .query("SELECT value FROM fs_config WHERE key = 'chunk_size'", ())
-            .await?;
-
-        let row = rows.next().await?.expect("chunk_size config should exist");
-        let value = row
-            .get_value(0)
-            .ok()
-            .and_then(|v| match v {
-                Value::Text(s) => Some(s.clone()),
-                _ => None,
-            })
-            .expect("chunk_size should be a text value");
-
-        assert_eq!(value, "4096");
+        assert!(chunk_count >= 1);
 
         Ok(())
     }
@@ -1230,13 +1721,7 @@ mod tests {
         let ino = fs.resolve_path("/unique.txt").await?.unwrap();
 
         // Try to insert a duplicate chunk - should fail due to PRIMARY KEY constraint
-        let result = fs
-            .conn
-            .execute(
-                "INSERT INTO fs_data (ino, chunk_index, data) VALUES (?, 0, ?)",
-                (ino, vec![1u8; 10]),
-            )
-            .await;
+        let result = fs.backend.insert_chunk(ino, 0, &[0u8; 32]).await;
 
         assert!(result.is_err(), "Duplicate chunk_index should be rejected");
 
@@ -1248,113 +1733,858 @@ mod tests {
         let (fs, _dir) = create_test_fs().await?;
 
         let chunk_size = fs.chunk_size();
-        // Create 5 chunks with identifiable data
+        // Several chunk_size's worth of data. This is still under
+        // CDC_MAX_CHUNK_SIZE, so CDC may store it as a single chunk; the
+        // check below holds for however many chunks it produced.
         let data_size = chunk_size * 5;
         let data: Vec<u8> = (0..data_size).map(|i| (i % 256) as u8).collect();
         fs.write_file("/ordered.bin", &data).await?;
 
         let ino = fs.resolve_path("/ordered.bin").await?.unwrap();
 
-        // Query chunks in order
-        let mut rows = fs
-            .conn
-            .query(
-                "SELECT chunk_index FROM fs_data WHERE ino = ? ORDER BY chunk_index",
-                (ino,),
-            )
-            .await?;
-
-        let mut indices = Vec::new();
-        while let Some(row) = rows.next().await? {
-            let idx = row
-                .get_value(0)
-                .ok()
-                .and_then(|v| v.as_integer().copied())
-                .unwrap_or(-1);
-            indices.push(idx);
-        }
+        // Chunk layout is already returned ordered by chunk_index.
+        let indices: Vec<i64> = fs
+            .backend
+            .chunk_layout(ino)
+            .await?
+            .into_iter()
+            .map(|(chunk_index, _, _)| chunk_index)
+            .collect();
 
-        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+        // Chunk indices must be contiguous starting at 0, in ascending order.
+        assert!(!indices.is_empty());
+        let expected: Vec<i64> = (0..indices.len() as i64).collect();
+        assert_eq!(indices, expected);
 
         Ok(())
     }
 
-    // ==================== Cleanup Tests ====================
+    // ==================== Partial I/O Tests ====================
 
     #[tokio::test]
-    async fn test_delete_file_removes_all_chunks() -> Result<()> {
+    async fn test_read_at_within_single_chunk() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let data: Vec<u8> = (0..100).collect();
+        fs.write_file("/partial.bin", &data).await?;
+
+        let read = fs.read_at("/partial.bin", 10, 20).await?.unwrap();
+        assert_eq!(read, data[10..30]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_at_spanning_chunks() -> Result<()> {
         let (fs, _dir) = create_test_fs().await?;
 
         let chunk_size = fs.chunk_size();
-        // Create multi-chunk file
-        let data = vec![0u8; chunk_size * 4];
-        fs.write_file("/deleteme.txt", &data).await?;
+        let data: Vec<u8> = (0..chunk_size * 3).map(|i| (i % 256) as u8).collect();
+        fs.write_file("/spanning.bin", &data).await?;
 
-        let ino = fs.resolve_path("/deleteme.txt").await?.unwrap();
-        assert_eq!(fs.get_chunk_count(ino).await?, 4);
+        let start = chunk_size - 10;
+        let len = 20;
+        let read = fs.read_at("/spanning.bin", start, len).await?.unwrap();
+        assert_eq!(read, data[start..start + len]);
 
-        // Delete the file
-        fs.remove("/deleteme.txt").await?;
+        Ok(())
+    }
 
-        // Verify all chunks are gone
-        let mut rows = fs
-            .conn
-            .query("SELECT COUNT(*) FROM fs_data WHERE ino = ?", (ino,))
-            .await?;
+    #[tokio::test]
+    async fn test_write_at_patches_middle_of_chunk() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
 
-        let count = rows
-            .next()
-            .await?
-            .and_then(|r| r.get_value(0).ok().and_then(|v| v.as_integer().copied()))
-            .unwrap_or(-1);
+        let data = vec![0u8; 100];
+        fs.write_file("/patch.bin", &data).await?;
 
-        assert_eq!(count, 0, "All chunks should be deleted");
+        fs.write_at("/patch.bin", 10, &[1, 2, 3, 4]).await?;
+
+        let read = fs.read_file("/patch.bin").await?.unwrap();
+        assert_eq!(&read[10..14], &[1, 2, 3, 4]);
+        assert_eq!(read.len(), 100);
+        // Bytes outside the patched range are untouched.
+        assert_eq!(read[9], 0);
+        assert_eq!(read[14], 0);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_multiple_files_different_sizes() -> Result<()> {
+    async fn test_write_at_only_touches_overlapping_chunks() -> Result<()> {
         let (fs, _dir) = create_test_fs().await?;
 
         let chunk_size = fs.chunk_size();
+        let data = vec![0u8; chunk_size * 3];
+        fs.write_file("/sparse_patch.bin", &data).await?;
 
-        // Create files of various sizes
-        let files = vec![
-            ("/tiny.txt", 10),
-            ("/small.txt", chunk_size / 2),
-            ("/exact.txt", chunk_size),
-            ("/medium.txt", chunk_size * 2 + 100),
-            ("/large.txt", chunk_size * 5),
-        ];
+        let ino = fs.resolve_path("/sparse_patch.bin").await?.unwrap();
+        let chunk_count_before = fs.get_chunk_count(ino).await?;
 
-        for (path, size) in &files {
-            let data: Vec<u8> = (0..*size).map(|i| (i % 256) as u8).collect();
-            fs.write_file(path, &data).await?;
-        }
+        fs.write_at("/sparse_patch.bin", chunk_size + 5, &[9, 9, 9])
+            .await?;
 
-        // Verify each file has correct data and chunk count
-        for (path, size) in &files {
-            let read_data = fs.read_file(path).await?.unwrap();
-            assert_eq!(read_data.len(), *size, "Size mismatch for {}", path);
+        // A patch entirely inside the file's existing range rewrites the
+        // chunk(s) it overlaps in place; it never appends new chunks.
+        let chunk_count_after = fs.get_chunk_count(ino).await?;
+        assert_eq!(chunk_count_after, chunk_count_before);
 
-            let expected_data: Vec<u8> = (0..*size).map(|i| (i % 256) as u8).collect();
-            assert_eq!(read_data, expected_data, "Data mismatch for {}", path);
+        let read = fs.read_at("/sparse_patch.bin", chunk_size + 5, 3).await?.unwrap();
+        assert_eq!(read, vec![9, 9, 9]);
+
+        // Bytes outside the patched range are untouched.
+        let before = fs.read_at("/sparse_patch.bin", 0, chunk_size + 5).await?.unwrap();
+        assert_eq!(before, vec![0u8; chunk_size + 5]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_at_grows_file_and_zero_fills_gap() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.write_file("/grow_sparse.bin", &[1, 2, 3]).await?;
+        fs.write_at("/grow_sparse.bin", 10, &[9, 9]).await?;
+
+        let read = fs.read_file("/grow_sparse.bin").await?.unwrap();
+        assert_eq!(read.len(), 12);
+        assert_eq!(&read[0..3], &[1, 2, 3]);
+        assert_eq!(&read[3..10], &[0u8; 7]);
+        assert_eq!(&read[10..12], &[9, 9]);
+
+        let stats = fs.stat("/grow_sparse.bin").await?.unwrap();
+        assert_eq!(stats.size, 12);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_truncate_shrinks_and_trims_final_chunk() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let chunk_size = fs.chunk_size();
+        let data: Vec<u8> = (0..chunk_size * 2 + 50).map(|i| (i % 256) as u8).collect();
+        fs.write_file("/shrink.bin", &data).await?;
+
+        fs.truncate("/shrink.bin", chunk_size + 10).await?;
+
+        let read = fs.read_file("/shrink.bin").await?.unwrap();
+        assert_eq!(read.len(), chunk_size + 10);
+        assert_eq!(read, data[..chunk_size + 10]);
+
+        let stats = fs.stat("/shrink.bin").await?.unwrap();
+        assert_eq!(stats.size, (chunk_size + 10) as i64);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_truncate_to_zero_removes_all_chunks() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let chunk_size = fs.chunk_size();
+        fs.write_file("/empty_me.bin", &vec![1u8; chunk_size * 2])
+            .await?;
+
+        fs.truncate("/empty_me.bin", 0).await?;
+
+        let ino = fs.resolve_path("/empty_me.bin").await?.unwrap();
+        assert_eq!(fs.get_chunk_count(ino).await?, 0);
+        assert_eq!(fs.dedup_stats().await?.block_count, 0);
+
+        let stats = fs.stat("/empty_me.bin").await?.unwrap();
+        assert_eq!(stats.size, 0);
+
+        Ok(())
+    }
+
+    // ==================== Cleanup Tests ====================
+
+    #[tokio::test]
+    async fn test_delete_file_removes_all_chunks() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let chunk_size = fs.chunk_size();
+        // Create multi-chunk file
+        let data = vec![0u8; chunk_size * 4];
+        fs.write_file("/deleteme.txt", &data).await?;
+
+        let ino = fs.resolve_path("/deleteme.txt").await?.unwrap();
+        assert!(fs.get_chunk_count(ino).await? >= 1);
+
+        // Delete the file
+        fs.remove("/deleteme.txt").await?;
+
+        // Verify all chunks are gone
+        assert_eq!(
+            fs.backend.chunk_layout(ino).await?.len(),
+            0,
+            "All chunks should be deleted"
+        );
+
+        // Deleting the only file referencing its blocks must release them too.
+        assert_eq!(fs.dedup_stats().await?.block_count, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multiple_files_different_sizes() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let chunk_size = fs.chunk_size();
+
+        // Create files of various sizes
+        let files = vec![
+            ("/tiny.txt", 10),
+            ("/small.txt", chunk_size / 2),
+            ("/exact.txt", chunk_size),
+            ("/medium.txt", chunk_size * 2 + 100),
+            ("/large.txt", chunk_size * 5),
+        ];
+
+        for (path, size) in &files {
+            let data: Vec<u8> = (0..*size).map(|i| (i % 256) as u8).collect();
+            fs.write_file(path, &data).await?;
+        }
+
+        // Verify each file has correct data and a sane chunk count
+        for (path, size) in &files {
+            let read_data = fs.read_file(path).await?.unwrap();
+            assert_eq!(read_data.len(), *size, "Size mismatch for {}", path);
+
+            let expected_data: Vec<u8> = (0..*size).map(|i| (i % 256) as u8).collect();
+            assert_eq!(read_data, expected_data, "Data mismatch for {}", path);
 
-            let expected_chunks = if *size == 0 {
-                0
-            } else {
-                (size + chunk_size - 1) / chunk_size
-            };
             let ino = fs.resolve_path(path).await?.unwrap();
-            let actual_chunks = fs.get_chunk_count(ino).await? as usize;
-            assert_eq!(
-                actual_chunks, expected_chunks,
-                "Chunk count mismatch for {}",
-                path
-            );
+            let actual_chunks = fs.get_chunk_count(ino).await?;
+            if *size == 0 {
+                assert_eq!(actual_chunks, 0, "Empty file should have no chunks for {}", path);
+            } else {
+                assert!(actual_chunks >= 1, "Non-empty file should have a chunk for {}", path);
+            }
         }
 
         Ok(())
     }
+
+    // ==================== Deduplication Tests ====================
+
+    #[tokio::test]
+    async fn test_identical_files_share_blocks() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let data = vec![0xABu8; 50_000];
+        fs.write_file("/a.bin", &data).await?;
+        let stats_after_one = fs.dedup_stats().await?;
+
+        fs.write_file("/b.bin", &data).await?;
+        let stats_after_two = fs.dedup_stats().await?;
+
+        // A second file with byte-identical content must not grow the
+        // physical block store or block count at all.
+        assert_eq!(stats_after_two.block_count, stats_after_one.block_count);
+        assert_eq!(stats_after_two.physical_bytes, stats_after_one.physical_bytes);
+        // But the logical size (what `read_file` returns across all files)
+        // has doubled.
+        assert_eq!(
+            stats_after_two.logical_bytes,
+            stats_after_one.logical_bytes * 2
+        );
+
+        assert_eq!(fs.read_file("/a.bin").await?.unwrap(), data);
+        assert_eq!(fs.read_file("/b.bin").await?.unwrap(), data);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_overwriting_with_same_content_does_not_leak_blocks() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let data = vec![0x42u8; 20_000];
+        fs.write_file("/repeat.bin", &data).await?;
+        let stats_first = fs.dedup_stats().await?;
+
+        // Rewriting with byte-identical content should converge back to the
+        // same block set instead of accumulating orphaned duplicates.
+        fs.write_file("/repeat.bin", &data).await?;
+        let stats_second = fs.dedup_stats().await?;
+
+        assert_eq!(stats_second.block_count, stats_first.block_count);
+        assert_eq!(stats_second.physical_bytes, stats_first.physical_bytes);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_removing_one_of_two_shared_files_keeps_block_alive() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let data = vec![0x7Cu8; 50_000];
+        fs.write_file("/a.bin", &data).await?;
+        fs.write_file("/b.bin", &data).await?;
+        let stats_shared = fs.dedup_stats().await?;
+
+        // Removing one of two files referencing the same blocks must only
+        // drop their refcount, not delete them out from under the survivor.
+        fs.remove("/a.bin").await?;
+        let stats_after_one_removed = fs.dedup_stats().await?;
+        assert_eq!(stats_after_one_removed.block_count, stats_shared.block_count);
+        assert_eq!(
+            stats_after_one_removed.physical_bytes,
+            stats_shared.physical_bytes
+        );
+        assert_eq!(fs.read_file("/b.bin").await?.unwrap(), data);
+
+        // Removing the last reference must finally free the blocks.
+        fs.remove("/b.bin").await?;
+        let stats_after_both_removed = fs.dedup_stats().await?;
+        assert_eq!(stats_after_both_removed.block_count, 0);
+        assert_eq!(stats_after_both_removed.physical_bytes, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fsck_reports_nothing_for_healthy_fs() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mkdir("/d").await?;
+        fs.write_file("/d/f.txt", b"hello").await?;
+
+        assert_eq!(fs.fsck(false).await?, FsckReport::default());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_stats_matches_dedup_stats() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hello").await?;
+
+        assert_eq!(fs.storage_stats().await?, fs.dedup_stats().await?);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gc_is_a_noop_on_healthy_fs() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hello").await?;
+
+        assert_eq!(fs.gc().await?, 0);
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dedup_stats_empty_fs_is_zero() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let stats = fs.dedup_stats().await?;
+        assert_eq!(stats.block_count, 0);
+        assert_eq!(stats.logical_bytes, 0);
+        assert_eq!(stats.physical_bytes, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_large_compressible_file_round_trips() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        let chunk_size = fs.chunk_size();
+        let data = vec![b'a'; chunk_size * 3];
+        fs.write_file("/big.txt", &data).await?;
+
+        assert_eq!(fs.read_file("/big.txt").await?.unwrap(), data);
+
+        let stats = fs.dedup_stats().await?;
+        assert_eq!(stats.logical_bytes, data.len() as i64);
+        assert!(stats.physical_bytes < stats.logical_bytes);
+        assert!(stats.compression_ratio() > 1.0);
+
+        Ok(())
+    }
+
+    // ==================== Path Cache Tests ====================
+
+    #[tokio::test]
+    async fn test_lookup_after_cache_populated_still_resolves() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mkdir("/dir").await?;
+        fs.write_file("/dir/file.txt", b"hello").await?;
+
+        // First lookup populates the cache; the second must hit it and
+        // still return the same inode.
+        let first = fs.stat("/dir/file.txt").await?.unwrap();
+        let second = fs.stat("/dir/file.txt").await?.unwrap();
+        assert_eq!(first.ino, second.ino);
+        assert_eq!(fs.read_file("/dir/file.txt").await?.unwrap(), b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_invalidates_cached_path() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.write_file("/gone.txt", b"data").await?;
+        fs.stat("/gone.txt").await?; // populate the cache
+        fs.remove("/gone.txt").await?;
+
+        assert!(fs.stat("/gone.txt").await?.is_none());
+
+        // Recreating the path must not resolve to the stale cached inode.
+        fs.write_file("/gone.txt", b"new").await?;
+        assert_eq!(fs.read_file("/gone.txt").await?.unwrap(), b"new");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_remove_invalidates_cached_descendants() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mkdir("/dir").await?;
+        fs.write_file("/dir/a.txt", b"a").await?;
+        fs.write_file("/dir/ab.txt", b"ab").await?;
+        // Populate the cache for both children plus a sibling that merely
+        // shares a name prefix with "/dir" and must NOT be invalidated.
+        fs.write_file("/dirty.txt", b"sibling").await?;
+        fs.stat("/dir/a.txt").await?;
+        fs.stat("/dir/ab.txt").await?;
+        fs.stat("/dirty.txt").await?;
+
+        fs.remove("/dir/a.txt").await?;
+        fs.remove("/dir/ab.txt").await?;
+        fs.remove("/dir").await?;
+
+        assert!(fs.stat("/dir").await?.is_none());
+        // The sibling path that only shares a string prefix with "/dir"
+        // (not a "/dir/" path prefix) must be unaffected.
+        assert_eq!(fs.read_file("/dirty.txt").await?.unwrap(), b"sibling");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_clear_cache_forces_fresh_resolution() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.write_file("/f.txt", b"one").await?;
+        fs.stat("/f.txt").await?;
+        fs.clear_cache();
+
+        // Still resolvable after clearing; clearing only drops the cache,
+        // not the underlying data.
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"one");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_disabling_cache_still_resolves_paths() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.set_cache_enabled(false);
+        fs.write_file("/f.txt", b"one").await?;
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"one");
+
+        fs.set_cache_enabled(true);
+        assert_eq!(fs.read_file("/f.txt").await?.unwrap(), b"one");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cache_evicts_least_recently_used_entry() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.path_cache.lock().unwrap().capacity = 2;
+
+        fs.write_file("/a.txt", b"a").await?;
+        fs.write_file("/b.txt", b"b").await?;
+        fs.write_file("/c.txt", b"c").await?;
+
+        // Populate the cache in order a, b, c. With capacity 2 this evicts
+        // "/a.txt" (the least recently used), but it must still resolve via
+        // a fresh database lookup rather than failing.
+        assert_eq!(fs.read_file("/a.txt").await?.unwrap(), b"a");
+        assert_eq!(fs.read_file("/b.txt").await?.unwrap(), b"b");
+        assert_eq!(fs.read_file("/c.txt").await?.unwrap(), b"c");
+
+        // Only the two most recently used paths are still cached.
+        let cache = fs.path_cache.lock().unwrap();
+        assert!(!cache.entries.contains_key("/a.txt"));
+        assert!(cache.entries.contains_key("/b.txt"));
+        assert!(cache.entries.contains_key("/c.txt"));
+
+        Ok(())
+    }
+
+    // ==================== Metadata Mutation Tests ====================
+
+    #[tokio::test]
+    async fn test_chmod_preserves_file_type_bits() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.chmod("/f.txt", 0o600).await?;
+
+        let stats = fs.stat("/f.txt").await?.unwrap();
+        assert!(stats.is_file());
+        assert_eq!(stats.mode & 0o7777, 0o600);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chmod_on_directory_preserves_dir_bit() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mkdir("/d").await?;
+
+        fs.chmod("/d", 0o700).await?;
+
+        let stats = fs.stat("/d").await?.unwrap();
+        assert!(stats.is_directory());
+        assert_eq!(stats.mode & 0o7777, 0o700);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chown_updates_owner() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.chown("/f.txt", 1000, 1000).await?;
+
+        let stats = fs.stat("/f.txt").await?.unwrap();
+        assert_eq!(stats.uid, 1000);
+        assert_eq!(stats.gid, 1000);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_utimens_updates_atime_and_mtime() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.utimens("/f.txt", 111, 222).await?;
+
+        let stats = fs.stat("/f.txt").await?.unwrap();
+        assert_eq!(stats.atime, 111);
+        assert_eq!(stats.mtime, 222);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mutating_metadata_on_missing_path_errors() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        assert!(fs.chmod("/nope.txt", 0o644).await.is_err());
+        assert!(fs.chown("/nope.txt", 0, 0).await.is_err());
+        assert!(fs.utimens("/nope.txt", 0, 0).await.is_err());
+
+        Ok(())
+    }
+
+    // ==================== Extended Attribute Tests ====================
+
+    #[tokio::test]
+    async fn test_setxattr_then_getxattr_roundtrips() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.setxattr("/f.txt", "user.provenance", b"agent-7").await?;
+
+        assert_eq!(
+            fs.getxattr("/f.txt", "user.provenance").await?,
+            Some(b"agent-7".to_vec())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_getxattr_missing_attr_returns_none() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        assert_eq!(fs.getxattr("/f.txt", "user.missing").await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_setxattr_overwrites_existing_value() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.setxattr("/f.txt", "user.tag", b"v1").await?;
+        fs.setxattr("/f.txt", "user.tag", b"v2").await?;
+
+        assert_eq!(
+            fs.getxattr("/f.txt", "user.tag").await?,
+            Some(b"v2".to_vec())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_listxattr_returns_sorted_names() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.setxattr("/f.txt", "user.b", b"2").await?;
+        fs.setxattr("/f.txt", "user.a", b"1").await?;
+
+        assert_eq!(
+            fs.listxattr("/f.txt").await?,
+            vec!["user.a".to_string(), "user.b".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_removexattr_drops_attribute() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+
+        fs.setxattr("/f.txt", "user.tag", b"v1").await?;
+        fs.removexattr("/f.txt", "user.tag").await?;
+
+        assert_eq!(fs.getxattr("/f.txt", "user.tag").await?, None);
+        assert!(fs.listxattr("/f.txt").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_removing_file_drops_its_xattrs() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hi").await?;
+        fs.setxattr("/f.txt", "user.tag", b"v1").await?;
+
+        let ino = fs.resolve_path("/f.txt").await?.unwrap();
+        fs.remove("/f.txt").await?;
+
+        assert!(fs.backend.list_xattrs(ino).await?.is_empty());
+
+        Ok(())
+    }
+
+    // ==================== Special File Type Tests ====================
+
+    #[tokio::test]
+    async fn test_mknod_char_device_stores_rdev() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mknod("/null", S_IFCHR | 0o666, 259).await?;
+
+        let stats = fs.stat("/null").await?.unwrap();
+        assert!(stats.is_char_device());
+        assert_eq!(stats.rdev, 259);
+        assert_eq!(stats.mode & 0o7777, 0o666);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mknod_block_device_stores_rdev() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mknod("/sda", S_IFBLK | 0o660, 2048).await?;
+
+        let stats = fs.stat("/sda").await?.unwrap();
+        assert!(stats.is_block_device());
+        assert_eq!(stats.rdev, 2048);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mknod_fifo_stores_no_data() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mknod("/pipe", S_IFIFO | 0o644, 0).await?;
+        let ino = fs.resolve_path("/pipe").await?.unwrap();
+
+        let stats = fs.stat("/pipe").await?.unwrap();
+        assert!(stats.is_fifo());
+        assert_eq!(stats.size, 0);
+        assert_eq!(fs.backend.chunk_layout(ino).await?.len(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mknod_socket() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        fs.mknod("/sock", S_IFSOCK | 0o755, 0).await?;
+
+        let stats = fs.stat("/sock").await?.unwrap();
+        assert!(stats.is_socket());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mknod_rejects_non_device_mode() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let result = fs.mknod("/regular", S_IFREG | 0o644, 0).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mknod_rejects_existing_path() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mknod("/null", S_IFCHR | 0o666, 259).await?;
+
+        let result = fs.mknod("/null", S_IFCHR | 0o666, 259).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_removing_device_node_drops_its_xattrs() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mknod("/null", S_IFCHR | 0o666, 259).await?;
+        fs.setxattr("/null", "user.tag", b"v1").await?;
+
+        let ino = fs.resolve_path("/null").await?.unwrap();
+        fs.remove("/null").await?;
+
+        assert!(fs.backend.list_xattrs(ino).await?.is_empty());
+
+        Ok(())
+    }
+
+    // ==================== Archive Tests ====================
+
+    #[tokio::test]
+    async fn test_export_then_import_archive_round_trips_tree() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mkdir("/d").await?;
+        fs.write_file("/d/f.txt", b"hello, archive").await?;
+        fs.symlink("/d/f.txt", "/d/link").await?;
+        fs.chmod("/d/f.txt", 0o600).await?;
+
+        let mut buf = Vec::new();
+        fs.export_archive(&mut buf).await?;
+
+        let (fs2, _dir2) = create_test_fs().await?;
+        fs2.import_archive(&mut buf.as_slice()).await?;
+
+        assert_eq!(
+            fs2.read_file("/d/f.txt").await?.unwrap(),
+            b"hello, archive"
+        );
+        assert_eq!(fs2.readlink("/d/link").await?.unwrap(), "/d/f.txt");
+        assert_eq!(fs2.stat("/d/f.txt").await?.unwrap().mode & 0o7777, 0o600);
+        assert!(fs2.lstat("/d").await?.unwrap().is_directory());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_archive_reads_index_without_content() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mkdir("/d").await?;
+        fs.write_file("/d/f.txt", b"hello").await?;
+
+        let mut buf = Vec::new();
+        fs.export_archive(&mut buf).await?;
+
+        let entries = Filesystem::list_archive(&mut buf.as_slice())?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "/d");
+        assert_eq!(entries[0].kind, ArchiveEntryKind::Directory);
+        assert_eq!(entries[1].path, "/d/f.txt");
+        assert_eq!(entries[1].kind, ArchiveEntryKind::File);
+        assert_eq!(entries[1].data_len, 5);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_archive_round_trips_device_node() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mknod("/null", S_IFCHR | 0o666, 259).await?;
+
+        let mut buf = Vec::new();
+        fs.export_archive(&mut buf).await?;
+
+        let entries = Filesystem::list_archive(&mut buf.as_slice())?;
+        assert_eq!(entries[0].kind, ArchiveEntryKind::Special);
+        assert_eq!(entries[0].rdev, 259);
+
+        let (fs2, _dir2) = create_test_fs().await?;
+        fs2.import_archive(&mut buf.as_slice()).await?;
+
+        let stats = fs2.lstat("/null").await?.unwrap();
+        assert!(stats.is_char_device());
+        assert_eq!(stats.rdev, 259);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_archive_rejects_bad_magic() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+
+        let result = fs.import_archive(&mut &b"not-an-archive"[..]).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_archive_rejects_truncated_data() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.write_file("/f.txt", b"hello").await?;
+        fs.write_file("/g.txt", b"world").await?;
+
+        let mut buf = Vec::new();
+        fs.export_archive(&mut buf).await?;
+        buf.truncate(buf.len() - 3); // Cut off the tail of the data region.
+
+        let (fs2, _dir2) = create_test_fs().await?;
+        let result = fs2.import_archive(&mut buf.as_slice()).await;
+        assert!(result.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_import_archive_rolls_back_on_conflicting_entry() -> Result<()> {
+        let (fs, _dir) = create_test_fs().await?;
+        fs.mkdir("/a").await?;
+        fs.mkdir("/b").await?;
+
+        let mut buf = Vec::new();
+        fs.export_archive(&mut buf).await?;
+
+        // "/a" sorts (and so imports) before the conflicting "/b", so a
+        // partial import would otherwise have already created it.
+        let (fs2, _dir2) = create_test_fs().await?;
+        fs2.mkdir("/b").await?;
+
+        let result = fs2.import_archive(&mut buf.as_slice()).await;
+        assert!(result.is_err());
+
+        // The rolled-back transaction must not have left "/a" behind either.
+        assert!(fs2.stat("/a").await?.is_none());
+
+        Ok(())
+    }
 }
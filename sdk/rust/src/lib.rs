@@ -0,0 +1,11 @@
+//! SQLite-backed filesystem core, with an optional FUSE mount on top.
+
+mod backend;
+mod cursor;
+mod filesystem;
+mod mount;
+
+pub use backend::{InodeRecord, SqliteBackend, VfsBackend};
+pub use cursor::FileCursor;
+pub use filesystem::{ArchiveEntry, ArchiveEntryKind, DedupStats, Filesystem, FsckReport, Stats};
+pub use mount::{mount, FuseFs};
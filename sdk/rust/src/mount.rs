@@ -0,0 +1,385 @@
+//! FUSE mount support for [`Filesystem`](crate::Filesystem).
+//!
+//! Exposes the SQLite-backed [`Filesystem`] through a real FUSE daemon
+//! using the `fuser` crate, so an agentfs database can be mounted at an
+//! ordinary path and used by arbitrary programs.
+//!
+//! FUSE addresses everything by inode number, while [`Filesystem`] is
+//! path-addressed, so this module keeps a small ino -> path cache seeded
+//! at the root and grown on every `lookup`/`mkdir`/`create`/`symlink`.
+//! FUSE callbacks are synchronous, so each one blocks on a Tokio runtime
+//! handle to drive the underlying async `Filesystem` calls.
+
+use crate::{Filesystem, Stats};
+use fuser::{
+    FileAttr, FileType, Filesystem as FuseFilesystem, ReplyAttr, ReplyCreate, ReplyData,
+    ReplyDirectory, ReplyEmpty, ReplyEntry, ReplyWrite, Request,
+};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+const ROOT_INO: u64 = 1;
+const NAME_MAX: usize = 255;
+
+/// How long the kernel is allowed to cache attributes/entries before
+/// re-querying. Kept short since the backing store can change out from
+/// under the mount (e.g. another process writing via the SDK directly).
+const TTL: Duration = Duration::from_secs(1);
+
+/// Adapts a [`Filesystem`] to the synchronous `fuser::Filesystem` trait.
+pub struct FuseFs {
+    fs: Filesystem,
+    rt: tokio::runtime::Handle,
+    paths: Mutex<HashMap<u64, String>>,
+}
+
+impl FuseFs {
+    /// Wrap `fs` for mounting, driving its async calls on `rt`.
+    pub fn new(fs: Filesystem, rt: tokio::runtime::Handle) -> Self {
+        let mut paths = HashMap::new();
+        paths.insert(ROOT_INO, "/".to_string());
+        Self {
+            fs,
+            rt,
+            paths: Mutex::new(paths),
+        }
+    }
+
+    fn path_of(&self, ino: u64) -> Option<String> {
+        self.paths.lock().unwrap().get(&ino).cloned()
+    }
+
+    fn child_path(&self, parent: u64, name: &OsStr) -> Option<(String, String)> {
+        let parent_path = self.path_of(parent)?;
+        let name = name.to_str()?;
+        let child = if parent_path == "/" {
+            format!("/{name}")
+        } else {
+            format!("{parent_path}/{name}")
+        };
+        Some((parent_path, child))
+    }
+
+    fn remember(&self, ino: u64, path: String) {
+        self.paths.lock().unwrap().insert(ino, path);
+    }
+
+    fn to_file_attr(stats: &Stats) -> FileAttr {
+        let kind = if (stats.mode & S_IFMT) == S_IFDIR {
+            FileType::Directory
+        } else if (stats.mode & S_IFMT) == S_IFLNK {
+            FileType::Symlink
+        } else {
+            FileType::RegularFile
+        };
+
+        let size = stats.size.max(0) as u64;
+        FileAttr {
+            ino: stats.ino as u64,
+            size,
+            blocks: size.div_ceil(512),
+            atime: UNIX_EPOCH + Duration::from_secs(stats.atime.max(0) as u64),
+            mtime: UNIX_EPOCH + Duration::from_secs(stats.mtime.max(0) as u64),
+            ctime: UNIX_EPOCH + Duration::from_secs(stats.ctime.max(0) as u64),
+            crtime: UNIX_EPOCH,
+            kind,
+            perm: (stats.mode & 0o7777) as u16,
+            nlink: stats.nlink,
+            uid: stats.uid,
+            gid: stats.gid,
+            rdev: 0,
+            blksize: 512,
+            flags: 0,
+        }
+    }
+}
+
+impl FuseFilesystem for FuseFs {
+    fn lookup(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        if name.len() > NAME_MAX {
+            reply.error(libc::ENAMETOOLONG);
+            return;
+        }
+        let Some((_, child_path)) = self.child_path(parent, name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.rt.block_on(self.fs.lstat(&child_path)) {
+            Ok(Some(stats)) => {
+                self.remember(stats.ino as u64, child_path);
+                reply.entry(&TTL, &Self::to_file_attr(&stats), 0);
+            }
+            Ok(None) => reply.error(libc::ENOENT),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        let Some(path) = self.path_of(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.rt.block_on(self.fs.lstat(&path)) {
+            Ok(Some(stats)) => reply.attr(&TTL, &Self::to_file_attr(&stats)),
+            Ok(None) => reply.error(libc::ENOENT),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let Some(dir_path) = self.path_of(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        let names = match self.rt.block_on(self.fs.readdir(&dir_path)) {
+            Ok(Some(names)) => names,
+            Ok(None) => {
+                reply.error(libc::ENOENT);
+                return;
+            }
+            Err(_) => {
+                reply.error(libc::EIO);
+                return;
+            }
+        };
+
+        let mut entries = vec![(ino, FileType::Directory, ".".to_string())];
+        for name in names {
+            let child_path = if dir_path == "/" {
+                format!("/{name}")
+            } else {
+                format!("{dir_path}/{name}")
+            };
+            let Ok(Some(stats)) = self.rt.block_on(self.fs.lstat(&child_path)) else {
+                continue;
+            };
+            self.remember(stats.ino as u64, child_path);
+            let kind = if stats.is_directory() {
+                FileType::Directory
+            } else if stats.is_symlink() {
+                FileType::Symlink
+            } else {
+                FileType::RegularFile
+            };
+            entries.push((stats.ino as u64, kind, name));
+        }
+
+        for (i, (ino, kind, name)) in entries.into_iter().enumerate().skip(offset as usize) {
+            if reply.add(ino, (i + 1) as i64, kind, &name) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(path) = self.path_of(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self
+            .rt
+            .block_on(self.fs.read_at(&path, offset.max(0) as usize, size as usize))
+        {
+            Ok(Some(data)) => reply.data(&data),
+            Ok(None) => reply.error(libc::ENOENT),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn write(
+        &mut self,
+        _req: &Request,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        data: &[u8],
+        _write_flags: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        let Some(path) = self.path_of(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self
+            .rt
+            .block_on(self.fs.write_at(&path, offset.max(0) as usize, data))
+        {
+            Ok(()) => reply.written(data.len() as u32),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn mkdir(
+        &mut self,
+        _req: &Request,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        reply: ReplyEntry,
+    ) {
+        let Some((_, child_path)) = self.child_path(parent, name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        if self.rt.block_on(self.fs.mkdir(&child_path)).is_err() {
+            reply.error(libc::EIO);
+            return;
+        }
+
+        match self.rt.block_on(self.fs.lstat(&child_path)) {
+            Ok(Some(stats)) => {
+                self.remember(stats.ino as u64, child_path);
+                reply.entry(&TTL, &Self::to_file_attr(&stats), 0);
+            }
+            _ => reply.error(libc::EIO),
+        }
+    }
+
+    fn symlink(
+        &mut self,
+        _req: &Request,
+        parent: u64,
+        link_name: &OsStr,
+        target: &std::path::Path,
+        reply: ReplyEntry,
+    ) {
+        let Some((_, link_path)) = self.child_path(parent, link_name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+        let Some(target) = target.to_str() else {
+            reply.error(libc::EIO);
+            return;
+        };
+
+        if self
+            .rt
+            .block_on(self.fs.symlink(target, &link_path))
+            .is_err()
+        {
+            reply.error(libc::EIO);
+            return;
+        }
+
+        match self.rt.block_on(self.fs.lstat(&link_path)) {
+            Ok(Some(stats)) => {
+                self.remember(stats.ino as u64, link_path);
+                reply.entry(&TTL, &Self::to_file_attr(&stats), 0);
+            }
+            _ => reply.error(libc::EIO),
+        }
+    }
+
+    fn readlink(&mut self, _req: &Request, ino: u64, reply: ReplyData) {
+        let Some(path) = self.path_of(ino) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.rt.block_on(self.fs.readlink(&path)) {
+            Ok(Some(target)) => reply.data(target.as_bytes()),
+            Ok(None) => reply.error(libc::ENOENT),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn create(
+        &mut self,
+        _req: &Request,
+        parent: u64,
+        name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _flags: i32,
+        reply: ReplyCreate,
+    ) {
+        if name.len() > NAME_MAX {
+            reply.error(libc::ENAMETOOLONG);
+            return;
+        }
+        let Some((_, child_path)) = self.child_path(parent, name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        if self.rt.block_on(self.fs.write_file(&child_path, &[])).is_err() {
+            reply.error(libc::EIO);
+            return;
+        }
+
+        match self.rt.block_on(self.fs.lstat(&child_path)) {
+            Ok(Some(stats)) => {
+                self.remember(stats.ino as u64, child_path);
+                reply.created(&TTL, &Self::to_file_attr(&stats), 0, 0, 0);
+            }
+            _ => reply.error(libc::EIO),
+        }
+    }
+
+    fn unlink(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        let Some((_, path)) = self.child_path(parent, name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.rt.block_on(self.fs.remove(&path)) {
+            Ok(()) => reply.ok(),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+
+    fn rmdir(&mut self, _req: &Request, parent: u64, name: &OsStr, reply: ReplyEmpty) {
+        let Some((_, path)) = self.child_path(parent, name) else {
+            reply.error(libc::ENOENT);
+            return;
+        };
+
+        match self.rt.block_on(self.fs.remove(&path)) {
+            Ok(()) => reply.ok(),
+            Err(_) => reply.error(libc::EIO),
+        }
+    }
+}
+
+/// Mount `fs` at `mountpoint`, blocking until the mount is unmounted.
+pub fn mount(
+    fs: Filesystem,
+    mountpoint: &std::path::Path,
+    rt: tokio::runtime::Handle,
+) -> anyhow::Result<()> {
+    let options = vec![fuser::MountOption::FSName("agentfs".to_string())];
+    fuser::mount2(FuseFs::new(fs, rt), mountpoint, &options)?;
+    Ok(())
+}